@@ -0,0 +1,479 @@
+use crate::boundary::{Boundary, Edge};
+use crate::cell::Cell;
+use crate::elements::{Element, NOTHING};
+use crate::grid::{Grid, Vector2};
+
+// Largest neighborhood radius a rule can use, which keeps neighbor counts below 256
+const MAX_RADIUS: usize = 7;
+
+// A cellular automaton rule. Three notations are understood:
+// - Life-like rules in B/S notation, e.g. "B3/S23" for Conway's Game of Life. A dead cell with
+//   a number of live neighbors listed after B is born, and a live cell with a number of live
+//   neighbors listed after S survives.
+//   https://conwaylife.com/wiki/Life-like_cellular_automaton
+// - Generations rules, which add a state count after C, e.g. "B2/S/C3" for Brian's Brain.
+//   Instead of disappearing, cells that don't survive spend C - 2 generations dying. Dying
+//   cells don't count as live neighbors, but nothing can be born where they are.
+//   https://conwaylife.com/wiki/Generations
+// - Larger than Life rules, e.g. "R5,C0,M1,S34..58,B34..45,NM" for Bosco's Rule, where
+//   neighbors are counted in a square of the given radius and births and survivals are
+//   given as ranges. M1 counts a live cell as its own neighbor.
+//   https://conwaylife.com/wiki/Larger_than_Life
+#[derive(Clone, Copy, PartialEq)]
+pub struct AutomatonRule {
+    // Bit n is set if a cell with n live neighbors is born / survives
+    birth: [u64; 4],
+    survival: [u64; 4],
+    // Number of states including alive and dead, so 2 for rules without dying states
+    pub states: u8,
+    pub radius: usize,
+    pub include_center: bool,
+}
+
+impl AutomatonRule {
+    // Parse a rule string. This is a const fn so element definitions can be written with
+    // their rule string, and a malformed rule fails to compile rather than at runtime.
+    pub const fn parse(rule: &str) -> AutomatonRule {
+        let bytes = rule.as_bytes();
+        if !bytes.is_empty() && (bytes[0] == b'R' || bytes[0] == b'r') {
+            return Self::parse_larger_than_life(bytes);
+        }
+
+        let mut parsed = AutomatonRule {
+            birth: [0; 4],
+            survival: [0; 4],
+            states: 2,
+            radius: 1,
+            include_center: false,
+        };
+        let mut section = b' ';
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'B' | b'b' => section = b'B',
+                b'S' | b's' => section = b'S',
+                b'C' | b'c' => {
+                    let (states, next) = parse_number(bytes, i + 1);
+                    parsed.states = states as u8;
+                    i = next;
+                    continue;
+                }
+                b'/' => {}
+                digit @ b'0'..=b'8' => {
+                    let count = (digit - b'0') as usize;
+                    match section {
+                        b'B' => parsed.birth = with_range(parsed.birth, count, count),
+                        b'S' => parsed.survival = with_range(parsed.survival, count, count),
+                        _ => panic!("automaton rule must start with B or S"),
+                    }
+                }
+                _ => panic!("invalid character in automaton rule"),
+            }
+            i += 1;
+        }
+        if parsed.states < 2 {
+            parsed.states = 2;
+        }
+        parsed
+    }
+
+    const fn parse_larger_than_life(bytes: &[u8]) -> AutomatonRule {
+        let mut parsed = AutomatonRule {
+            birth: [0; 4],
+            survival: [0; 4],
+            states: 2,
+            radius: 1,
+            include_center: false,
+        };
+        let mut i = 0;
+        while i < bytes.len() {
+            let key = bytes[i];
+            i += 1;
+            match key {
+                b',' => {}
+                b'R' | b'r' => {
+                    let (radius, next) = parse_number(bytes, i);
+                    if radius < 1 || radius > MAX_RADIUS {
+                        panic!("automaton radius out of range");
+                    }
+                    parsed.radius = radius;
+                    i = next;
+                }
+                b'C' | b'c' => {
+                    let (states, next) = parse_number(bytes, i);
+                    parsed.states = if states < 2 { 2 } else { states as u8 };
+                    i = next;
+                }
+                b'M' | b'm' => {
+                    let (middle, next) = parse_number(bytes, i);
+                    parsed.include_center = middle == 1;
+                    i = next;
+                }
+                b'S' | b's' | b'B' | b'b' => {
+                    let (min, next) = parse_number(bytes, i);
+                    i = next;
+                    let mut max = min;
+                    if i + 1 < bytes.len() && bytes[i] == b'.' && bytes[i + 1] == b'.' {
+                        let (end, next) = parse_number(bytes, i + 2);
+                        max = end;
+                        i = next;
+                    }
+                    if key == b'S' || key == b's' {
+                        parsed.survival = with_range(parsed.survival, min, max);
+                    } else {
+                        parsed.birth = with_range(parsed.birth, min, max);
+                    }
+                }
+                b'N' | b'n' => {
+                    if i >= bytes.len() || (bytes[i] != b'M' && bytes[i] != b'm') {
+                        panic!("only the Moore neighborhood (NM) is supported");
+                    }
+                    i += 1;
+                }
+                _ => panic!("invalid character in automaton rule"),
+            }
+        }
+        parsed
+    }
+
+    pub fn is_born(&self, live_neighbors: usize) -> bool {
+        contains(&self.birth, live_neighbors)
+    }
+
+    pub fn survives(&self, live_neighbors: usize) -> bool {
+        contains(&self.survival, live_neighbors)
+    }
+}
+
+// Read the decimal number starting at bytes[i], returning it and the index just past it
+const fn parse_number(bytes: &[u8], mut i: usize) -> (usize, usize) {
+    let start = i;
+    let mut value = 0;
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        value = value * 10 + (bytes[i] - b'0') as usize;
+        i += 1;
+    }
+    if i == start {
+        panic!("expected a number in automaton rule");
+    }
+    (value, i)
+}
+
+const fn with_range(mut set: [u64; 4], min: usize, max: usize) -> [u64; 4] {
+    let mut n = min;
+    while n <= max {
+        if n >= 256 {
+            panic!("neighbor count out of range in automaton rule");
+        }
+        set[n / 64] |= 1 << (n % 64);
+        n += 1;
+    }
+    set
+}
+
+fn contains(set: &[u64; 4], n: usize) -> bool {
+    n < 256 && set[n / 64] & (1 << (n % 64)) != 0
+}
+
+// Advance every automaton on the grid by one generation. Each cell's fate is decided from the
+// grid as it was at the start of the generation and all changes are applied together, so
+// cells born or killed this generation don't affect their neighbors until the next one.
+pub fn step_automata(grid: &mut Grid) {
+    let changes = next_generation(grid);
+    for (pos, element, state) in changes {
+        grid.set(pos, element);
+        if let Some(cell) = grid.get_cell_mut(pos) {
+            cell.state = state;
+        }
+    }
+}
+
+fn next_generation(grid: &Grid) -> Vec<(Vector2, &'static Element, u8)> {
+    let cells = grid.cells();
+    let tables = NeighborCounts::build(grid, cells);
+    let mut changes = Vec::new();
+    if tables.is_empty() {
+        return changes;
+    }
+
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            let cell = &cells[y * grid.width + x];
+            if let Some(rule) = cell.element.rule {
+                if cell.state > 0 {
+                    // Dying cells fade out one state per generation
+                    let state = cell.state + 1;
+                    if state >= rule.states - 1 {
+                        changes.push(((x, y), &NOTHING, 0));
+                    } else {
+                        changes.push(((x, y), cell.element, state));
+                    }
+                    continue;
+                }
+                let table = tables.iter().find(|table| table.species == cell.element);
+                let live_neighbors = table.map_or(0, |table| table.count(x, y, true));
+                if !rule.survives(live_neighbors) {
+                    if rule.states > 2 {
+                        changes.push(((x, y), cell.element, 1));
+                    } else {
+                        changes.push(((x, y), &NOTHING, 0));
+                    }
+                }
+            } else if *cell.element == NOTHING {
+                // If several species could be born here the first one found wins
+                let born = tables.iter().find(|table| {
+                    let live_neighbors = table.count(x, y, false);
+                    live_neighbors > 0 && table.rule.is_born(live_neighbors)
+                });
+                if let Some(table) = born {
+                    changes.push(((x, y), table.species, 0));
+                }
+            }
+        }
+    }
+    changes
+}
+
+// Summed-area table of the live cells of one species, so the live cells in any square
+// neighborhood can be counted in constant time whatever its radius
+struct NeighborCounts {
+    species: &'static Element,
+    rule: AutomatonRule,
+    width: usize,
+    height: usize,
+    // Whether neighborhoods wrap around the edges of the world, sideways and vertically
+    wraps: (bool, bool),
+    sums: Vec<u32>,
+}
+
+impl NeighborCounts {
+    // One table for every species with live cells on the grid
+    fn build(grid: &Grid, cells: &[Cell]) -> Vec<NeighborCounts> {
+        let mut tables: Vec<NeighborCounts> = Vec::new();
+        for cell in cells {
+            if let Some(rule) = cell.element.rule {
+                if !tables.iter().any(|table| table.species == cell.element) {
+                    tables.push(NeighborCounts::new(grid, cells, cell.element, rule));
+                }
+            }
+        }
+        tables
+    }
+
+    fn new(
+        grid: &Grid,
+        cells: &[Cell],
+        species: &'static Element,
+        rule: AutomatonRule,
+    ) -> NeighborCounts {
+        let stride = grid.width + 1;
+        let mut sums = vec![0; stride * (grid.height + 1)];
+        for y in 0..grid.height {
+            let mut row = 0;
+            for x in 0..grid.width {
+                let cell = &cells[y * grid.width + x];
+                if cell.element == species && cell.state == 0 {
+                    row += 1;
+                }
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row;
+            }
+        }
+        NeighborCounts {
+            species,
+            rule,
+            width: grid.width,
+            height: grid.height,
+            wraps: (
+                grid.boundaries.get(Edge::Left) == Boundary::Wrap,
+                grid.boundaries.get(Edge::Top) == Boundary::Wrap,
+            ),
+            sums,
+        }
+    }
+
+    // Count the live cells within the rule's radius of (x, y). The cell itself is only
+    // counted if it is alive and the rule includes the center.
+    fn count(&self, x: usize, y: usize, is_alive: bool) -> usize {
+        let radius = self.rule.radius;
+        let columns = span(x, radius, self.width, self.wraps.0);
+        let rows = span(y, radius, self.height, self.wraps.1);
+        let mut count = 0;
+        for (x1, x2) in columns {
+            for (y1, y2) in rows {
+                count += self.sum(x1, y1, x2, y2) as usize;
+            }
+        }
+        if is_alive && !self.rule.include_center {
+            count -= 1;
+        }
+        count
+    }
+
+    // Live cells in the rectangle from (x1, y1) up to but not including (x2, y2)
+    fn sum(&self, x1: usize, y1: usize, x2: usize, y2: usize) -> u32 {
+        let stride = self.width + 1;
+        self.sums[y2 * stride + x2] + self.sums[y1 * stride + x1]
+            - self.sums[y1 * stride + x2]
+            - self.sums[y2 * stride + x1]
+    }
+}
+
+// The ranges of one coordinate within `radius` of `center`. Past a wrapping edge the range
+// carries on from the other side, so it may be split in two; otherwise it is cut off at the
+// edge and the second range is empty.
+fn span(center: usize, radius: usize, size: usize, wraps: bool) -> [(usize, usize); 2] {
+    let low = center as isize - radius as isize;
+    let high = (center + radius + 1) as isize;
+    let size = size as isize;
+    if !wraps {
+        return [(low.max(0) as usize, high.min(size) as usize), (0, 0)];
+    }
+    if high - low >= size {
+        [(0, size as usize), (0, 0)]
+    } else if low < 0 {
+        [((low + size) as usize, size as usize), (0, high as usize)]
+    } else if high > size {
+        [(low as usize, size as usize), (0, (high - size) as usize)]
+    } else {
+        [(low as usize, high as usize), (0, 0)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{BRIANS_BRAIN, HIGHLIFE, LIFE};
+    use crate::grid::{DEFAULT_HEIGHT, DEFAULT_WIDTH};
+
+    fn place(grid: &mut Grid, element: &'static Element, cells: &[Vector2]) {
+        for &pos in cells {
+            grid.set(pos, element);
+        }
+    }
+
+    fn live_cells(grid: &Grid, species: &Element) -> Vec<Vector2> {
+        let mut cells = Vec::new();
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                if grid.get((x, y)) == species {
+                    cells.push((x, y));
+                }
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn parses_rule_strings() {
+        let life = AutomatonRule::parse("B3/S23");
+        assert!(life.is_born(3));
+        assert!(!life.is_born(2));
+        assert!(life.survives(2) && life.survives(3));
+        assert!(!life.survives(4));
+    }
+
+    #[test]
+    fn parses_generations_and_larger_than_life_rules() {
+        let brians_brain = AutomatonRule::parse("B2/S/C3");
+        assert_eq!(brians_brain.states, 3);
+        assert!(brians_brain.is_born(2));
+        assert!(!brians_brain.survives(2));
+
+        let bosco = AutomatonRule::parse("R5,C0,M1,S34..58,B34..45,NM");
+        assert_eq!(bosco.radius, 5);
+        assert_eq!(bosco.states, 2);
+        assert!(bosco.include_center);
+        assert!(bosco.survives(34) && bosco.survives(58) && !bosco.survives(59));
+        assert!(bosco.is_born(34) && bosco.is_born(45) && !bosco.is_born(33));
+    }
+
+    #[test]
+    fn blinker_oscillates() {
+        let mut grid = Grid::new(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+        let horizontal = vec![(10, 11), (11, 11), (12, 11)];
+        let vertical = vec![(11, 10), (11, 11), (11, 12)];
+        place(&mut grid, &LIFE, &horizontal);
+
+        step_automata(&mut grid);
+        assert_eq!(live_cells(&grid, &LIFE), vertical);
+        step_automata(&mut grid);
+        assert_eq!(live_cells(&grid, &LIFE), horizontal);
+    }
+
+    #[test]
+    fn glider_moves_diagonally() {
+        let mut grid = Grid::new(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+        let glider = [(11, 10), (12, 11), (10, 12), (11, 12), (12, 12)];
+        place(&mut grid, &LIFE, &glider);
+
+        for _ in 0..4 {
+            step_automata(&mut grid);
+        }
+        let moved: Vec<Vector2> = glider.iter().map(|&(x, y)| (x + 1, y + 1)).collect();
+        let mut live = live_cells(&grid, &LIFE);
+        live.sort();
+        let mut expected = moved;
+        expected.sort();
+        assert_eq!(live, expected);
+    }
+
+    #[test]
+    fn glider_wraps_around_the_world() {
+        let mut grid = Grid::new(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+        grid.boundaries.set(Edge::Right, Boundary::Wrap);
+        grid.boundaries.set(Edge::Bottom, Boundary::Wrap);
+        // Starting in the bottom right corner, after 8 generations the glider has moved
+        // two cells diagonally and come back in at the top left
+        let (right, bottom) = (grid.width - 1, grid.height - 1);
+        let glider = [
+            (right - 1, bottom - 2),
+            (right, bottom - 1),
+            (right - 2, bottom),
+            (right - 1, bottom),
+            (right, bottom),
+        ];
+        place(&mut grid, &LIFE, &glider);
+
+        for _ in 0..8 {
+            step_automata(&mut grid);
+        }
+        let mut live = live_cells(&grid, &LIFE);
+        live.sort();
+        let mut expected: Vec<Vector2> = glider
+            .iter()
+            .map(|&(x, y)| ((x + 2) % grid.width, (y + 2) % grid.height))
+            .collect();
+        expected.sort();
+        assert_eq!(live, expected);
+    }
+
+    #[test]
+    fn species_ignore_each_other() {
+        let mut grid = Grid::new(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+        // A lone block of Life is stable, and a HighLife cell next to it doesn't disturb it
+        let block = [(10, 10), (11, 10), (10, 11), (11, 11)];
+        place(&mut grid, &LIFE, &block);
+        place(&mut grid, &HIGHLIFE, &[(12, 10)]);
+
+        step_automata(&mut grid);
+        assert_eq!(live_cells(&grid, &LIFE), block.to_vec());
+        assert!(live_cells(&grid, &HIGHLIFE).is_empty());
+    }
+
+    #[test]
+    fn generations_cells_fade_before_disappearing() {
+        let mut grid = Grid::new(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+        // Two Brian's Brain cells side by side: each gives birth above and below the pair,
+        // and then starts dying because nothing survives in this rule
+        place(&mut grid, &BRIANS_BRAIN, &[(10, 10), (11, 10)]);
+
+        step_automata(&mut grid);
+        let state = |grid: &Grid, pos| grid.get_cell(pos).unwrap().state;
+        assert_eq!(state(&grid, (10, 10)), 1);
+        assert_eq!(state(&grid, (10, 9)), 0);
+        assert!(*grid.get((10, 9)) == BRIANS_BRAIN);
+
+        step_automata(&mut grid);
+        assert!(*grid.get((10, 10)) == NOTHING);
+    }
+}
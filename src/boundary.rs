@@ -0,0 +1,99 @@
+// What happens to things that reach an edge of the world
+#[derive(Clone, Copy, PartialEq)]
+pub enum Boundary {
+    // Nothing gets past
+    Wall,
+    // Cells falling off the edge are deleted
+    Void,
+    // Cells leaving the edge come back in on the opposite edge
+    Wrap,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Edge {
+    pub const ALL: [Edge; 4] = [Edge::Left, Edge::Right, Edge::Top, Edge::Bottom];
+
+    fn opposite(self) -> Edge {
+        match self {
+            Edge::Left => Edge::Right,
+            Edge::Right => Edge::Left,
+            Edge::Top => Edge::Bottom,
+            Edge::Bottom => Edge::Top,
+        }
+    }
+
+    // The offset pointing out of the world through this edge
+    pub fn outward(self) -> (isize, isize) {
+        match self {
+            Edge::Left => (-1, 0),
+            Edge::Right => (1, 0),
+            Edge::Top => (0, -1),
+            Edge::Bottom => (0, 1),
+        }
+    }
+}
+
+// The boundary on each edge of the world. Wrapping always joins two opposite edges, so
+// an edge only wraps when the one across from it does too.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Boundaries {
+    pub left: Boundary,
+    pub right: Boundary,
+    pub top: Boundary,
+    pub bottom: Boundary,
+}
+
+impl Boundaries {
+    pub const WALLS: Boundaries = Boundaries {
+        left: Boundary::Wall,
+        right: Boundary::Wall,
+        top: Boundary::Wall,
+        bottom: Boundary::Wall,
+    };
+
+    pub fn get(&self, edge: Edge) -> Boundary {
+        match edge {
+            Edge::Left => self.left,
+            Edge::Right => self.right,
+            Edge::Top => self.top,
+            Edge::Bottom => self.bottom,
+        }
+    }
+
+    // Change the boundary on one edge, keeping wrapping edges paired up
+    pub fn set(&mut self, edge: Edge, boundary: Boundary) {
+        let opposite = edge.opposite();
+        if boundary == Boundary::Wrap {
+            *self.get_mut(opposite) = Boundary::Wrap;
+        } else if self.get(opposite) == Boundary::Wrap {
+            *self.get_mut(opposite) = Boundary::Wall;
+        }
+        *self.get_mut(edge) = boundary;
+    }
+
+    // Switch an edge from wall to void to wrap and back to wall
+    pub fn cycle(&mut self, edge: Edge) {
+        let next = match self.get(edge) {
+            Boundary::Wall => Boundary::Void,
+            Boundary::Void => Boundary::Wrap,
+            Boundary::Wrap => Boundary::Wall,
+        };
+        self.set(edge, next);
+    }
+
+    fn get_mut(&mut self, edge: Edge) -> &mut Boundary {
+        match edge {
+            Edge::Left => &mut self.left,
+            Edge::Right => &mut self.right,
+            Edge::Top => &mut self.top,
+            Edge::Bottom => &mut self.bottom,
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use ::rand::{thread_rng, Rng};
+use macroquad::prelude::*;
+
+use crate::electricity::CHARGE_DURATION;
+use crate::elements::{Element, PoweredEffect, NOTHING};
+
+// How much darker a saturated cell is drawn than a dry one
+const WET_DARKENING: f32 = 0.4;
+// Color charged conductors glow with
+const CHARGE_COLOR: Color = Color::new(1.0, 0.95, 0.4, 1.0);
+
+// A single slot in the grid: the element occupying it plus any state that
+// belongs to this particular cell rather than to the element as a whole.
+#[derive(Clone, Copy)]
+pub struct Cell {
+    pub element: &'static Element,
+    // Number of ticks this cell has existed for
+    pub age: u32,
+    // Number of ticks this cell lives for before decaying, if it decays at all
+    pub lifetime: Option<u32>,
+    // Velocity in cells per tick, positive y pointing down
+    pub velocity: (f32, f32),
+    // Matches the grid's clock once this cell has been moved during the current update
+    pub clock: bool,
+    // What a generator without an element of its own has picked up to emit
+    pub emits: Option<&'static Element>,
+    // Automata: generations spent dying, or 0 while alive
+    pub state: u8,
+    // Powders: whether the cell is still moving, or resting until something disturbs it.
+    // Liquids: whether the cell is still falling, or has landed.
+    pub free_falling: bool,
+    // Absorbent elements: how much liquid the cell has soaked up, from 0 (dry) to 1 (saturated)
+    pub moisture: f32,
+    // Id of the rigid body this cell is part of, if any
+    pub body: Option<u32>,
+    // Conductors: ticks left until the cell can take another charge, or 0 if it isn't charged
+    pub charge: u8,
+}
+
+impl Cell {
+    pub fn new(element: &'static Element) -> Cell {
+        let lifetime = element
+            .lifetime
+            .map(|lifetime| thread_rng().gen_range(lifetime.min..=lifetime.max));
+        Cell {
+            element,
+            age: 0,
+            lifetime,
+            velocity: (0.0, 0.0),
+            clock: false,
+            emits: None,
+            state: 0,
+            free_falling: true,
+            moisture: 0.0,
+            body: None,
+            charge: 0,
+        }
+    }
+
+    // The color to draw the cell with, if it is visible at all. Dying automaton cells fade
+    // out the closer they get to disappearing, wet cells are darker, and charged cells glow.
+    pub fn color(&self) -> Option<Color> {
+        let color = self.element.get_color()?;
+        let mut brightness = 1.0 - WET_DARKENING * self.moisture;
+        if let Some(rule) = self.element.rule {
+            brightness *= 1.0 - self.state as f32 / (rule.states - 1) as f32;
+        }
+        let glow = self.charge as f32 / CHARGE_DURATION as f32;
+        let mix = |channel: f32, charged: f32| channel * brightness * (1.0 - glow) + charged * glow;
+        Some(Color::new(
+            mix(color.r, CHARGE_COLOR.r),
+            mix(color.g, CHARGE_COLOR.g),
+            mix(color.b, CHARGE_COLOR.b),
+            color.a,
+        ))
+    }
+
+    // Whether the cell dries out and sets alight what touches it
+    pub fn is_hot(&self) -> bool {
+        self.element.hot || (self.is_powered() && self.element.powered == Some(PoweredEffect::Heat))
+    }
+
+    // Whether charge is passing through the cell
+    pub fn is_powered(&self) -> bool {
+        self.charge > 0
+    }
+
+    // Advance the age of the cell by one tick, decaying it if it has outlived its lifetime.
+    // Returns whether it decayed.
+    pub fn tick(&mut self) -> bool {
+        let Some(lifetime) = self.lifetime else {
+            return false;
+        };
+        self.age += 1;
+        if self.age < lifetime {
+            return false;
+        }
+        let decays_into = self.element.lifetime.map_or(&NOTHING, |l| l.decays_into);
+        *self = Cell::new(decays_into);
+        true
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell::new(&NOTHING)
+    }
+}
@@ -0,0 +1,69 @@
+use crate::cell::Cell;
+use crate::element_type::ignite;
+use crate::elements::PoweredEffect;
+use crate::grid::{Grid, Vector2};
+
+// Ticks a conductor stays charged once a charge reaches it. Only cells that were charged
+// this very tick pass the charge on, and only cells that have cooled back down to 0 can take
+// a new one, so charge travels along a wire as a pulse one cell per tick instead of
+// spreading back the way it came.
+pub const CHARGE_DURATION: u8 = 4;
+
+// Move every charge one cell further along its conductor and let powered components react.
+// Power sources charge the conductors touching them every tick they can take a charge.
+// Charge is pushed out from the cells giving it, so a grid with nothing charged costs a
+// single pass over the cells.
+pub fn step_electricity(grid: &mut Grid) {
+    let mut charging: Vec<Vector2> = Vec::new();
+    let mut any_charged = false;
+    for (index, cell) in grid.cells().iter().enumerate() {
+        any_charged |= cell.is_powered();
+        if is_charging(cell) {
+            charging.push((index % grid.width, index / grid.width));
+        }
+    }
+    if charging.is_empty() && !any_charged {
+        return;
+    }
+
+    let mut arriving: Vec<Vector2> = charging
+        .iter()
+        .flat_map(|&(x, y)| neighbors(grid, x, y))
+        .filter(|&pos| {
+            grid.get_cell(pos)
+                .is_some_and(|cell| cell.element.conductive && !cell.is_powered())
+        })
+        .collect();
+    arriving.sort_unstable();
+    arriving.dedup();
+
+    for cell in grid.cells_mut() {
+        cell.charge = cell.charge.saturating_sub(1);
+    }
+    for &(x, y) in &arriving {
+        if let Some(cell) = grid.get_cell_mut((x, y)) {
+            cell.charge = CHARGE_DURATION;
+        }
+        if grid.get((x, y)).powered == Some(PoweredEffect::Ignite) {
+            let flammable: Vec<Vector2> = neighbors(grid, x, y)
+                .filter(|&pos| grid.get(pos).flammability > 0.0)
+                .collect();
+            for pos in flammable {
+                ignite(grid, pos);
+            }
+        }
+    }
+}
+
+// Whether the cell charges the conductors touching it this tick
+fn is_charging(cell: &Cell) -> bool {
+    cell.element.power_source || (cell.element.conductive && cell.charge == CHARGE_DURATION)
+}
+
+// The positions surrounding (x, y) within the world, diagonals included
+fn neighbors(grid: &Grid, x: usize, y: usize) -> impl Iterator<Item = Vector2> + '_ {
+    (-1..=1)
+        .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+        .filter(|&offset| offset != (0, 0))
+        .filter_map(move |offset| grid.offset((x, y), offset))
+}
@@ -1,5 +1,7 @@
-use crate::elements::{Element, AIR, DRAIN, FIRE, MAZE, NOTHING, WATER};
-use crate::grid::Grid;
+use crate::elements::{Element, PoweredEffect, FIRE, FUMES, NOTHING, WATER};
+use crate::explosion::explode;
+use crate::grid::{Grid, Vector2};
+use crate::wind::wind_push;
 use ::rand::{thread_rng, Rng};
 use macroquad::prelude::*;
 #[derive(Clone, Copy, PartialEq)]
@@ -11,229 +13,757 @@ pub enum ElementType {
     Gas,
     PixelGenerator,
     PixelDestroyer,
-    Maze,
+    Automaton,
     Nothing,
     Fire,
+    Plant,
 }
 
+// Density of empty space, which gases rise or sink through depending on their own density
+pub const AMBIENT_DENSITY: f32 = 1.0;
+// Acceleration applied to falling cells every tick, in cells per tick
+pub const GRAVITY: f32 = 0.25;
+// Fastest a cell can fall, in cells per tick
+pub const TERMINAL_VELOCITY: f32 = 8.0;
+// How much of a flowing liquid's speed carries over to the next tick
+const FLOW_DAMPING: f32 = 0.8;
+// Speed a liquid has to land at before it can splash, and the chance that it does
+const SPLASH_VELOCITY: f32 = 3.0;
+const SPLASH_CHANCE: f64 = 0.3;
+// Chance per tick of an absorbent cell soaking up each neighboring liquid cell
+const ABSORB_CHANCE: f32 = 0.2;
+// Moisture lost per tick on its own, and next to something hot
+const DRYING_RATE: f32 = 0.0005;
+const HEATED_DRYING_RATE: f32 = 0.02;
+// Moisture above which powders clump together and stop sliding
+const CLUMPING_MOISTURE: f32 = 0.3;
+// Powders with less friction than this can slide sideways over more than one cell
+const LOW_FRICTION: f32 = 0.2;
+// Moisture a growing plant tip uses up for every cell it grows
+const GROWTH_COST: f32 = 0.05;
+// Fraction of the difference in moisture a plant cell draws from a wetter neighbor each tick
+const WICKING_RATE: f32 = 0.5;
+// Chance that a corrosive liquid is used up each time it dissolves something
+const CORROSION_SPENT_CHANCE: f32 = 0.5;
+// Chance per tick of a liquid next to something hot boiling
+const BOIL_CHANCE: f32 = 0.05;
+// Gases condense within this many cells of the top of the world, or against a cold surface,
+// with the given chance per tick
+const CONDENSATION_HEIGHT: usize = 20;
+const CONDENSE_CHANCE: f32 = 0.02;
+// How readily gases and fire are pushed around by the wind
+const GAS_WIND_RESPONSE: f32 = 0.5;
+
+// Powders fall under gravity and then slide down slopes. Once a grain stops sliding it
+// rests until something next to it moves and wakes it up again. Each tick a sliding grain
+// has a chance to come to rest based on its friction, so high friction powders pile up
+// steeply. Very low friction powders can also slide sideways over a few cells, so they
+// spread out into nearly flat heaps.
 pub fn step_moveable_solid(grid: &mut Grid, x: usize, y: usize) {
-    // Check if there is air below
-    if y + 1 < grid.height && grid.get((x, y + 1)) == NOTHING {
-        // Fall down
-        grid.move_element((x, y), (x, y + 1));
-    } else if y + 1 < grid.height && grid.get((x, y + 1)) == WATER {
-        // Swap with water below
-        grid.swap_elements((x, y), (x, y + 1));
+    // Powders lighter than air get blown about by the wind
+    let lightness = AMBIENT_DENSITY - grid.get((x, y)).density;
+    if lightness > 0.0 {
+        if let Some(target) = blown_target(grid, x, y, lightness) {
+            grid.move_element((x, y), target);
+            grid.set_free_falling(target, true);
+            return;
+        }
+    }
+
+    // Fall through empty space, picking up speed as we go
+    if let Some(end) = fall(grid, x, y) {
+        grid.set_free_falling(end, true);
+        return;
+    }
+    if grid
+        .get_cell((x, y))
+        .is_some_and(|cell| cell.element.moldable && cell.moisture > CLUMPING_MOISTURE)
+    {
+        // Wet enough to hold whatever shape it has been given once it lands
+        return;
+    }
+    let gravity = grid.gravity;
+    if gravity.is_zero_g() {
+        // Nothing to slide down
+        return;
+    }
+
+    let below = grid
+        .below((x, y))
+        .filter(|&below| grid.get(below).element_type == ElementType::Liquid);
+    if let Some(below) = below {
+        // Sink through liquid below, which slows the fall right down
+        let (across, down) = gravity.to_relative(grid.get_velocity((x, y)));
+        grid.swap_elements((x, y), below);
+        grid.set_velocity(below, gravity.from_relative((across, down.min(1.0))));
+        return;
+    }
+
+    // Landed, so lose all momentum and try sliding down a slope instead
+    grid.set_velocity((x, y), (0.0, 0.0));
+    let Some(cell) = grid.get_cell((x, y)) else {
+        return;
+    };
+    if !cell.free_falling || cell.moisture > CLUMPING_MOISTURE {
+        // Wet powders clump together rather than sliding
+        return;
+    }
+    let friction = cell.element.friction;
+    if thread_rng().gen::<f32>() < friction {
+        grid.set_free_falling((x, y), false);
+        return;
+    }
+
+    let reach = slide_reach(friction);
+    let mut options = Vec::new();
+    for direction in [-1, 1] {
+        if let Some(target) = slide_target(grid, x, y, direction, reach) {
+            options.push(target);
+        }
+    }
+
+    if options.is_empty() {
+        // Nowhere left to go, so settle until disturbed
+        grid.set_free_falling((x, y), false);
     } else {
-        let mut options = Vec::new();
+        let random_index = rand::gen_range(0, options.len());
+        grid.move_element((x, y), options[random_index]);
+    }
+}
+
+// How many cells sideways a powder can travel to get down one cell
+fn slide_reach(friction: f32) -> usize {
+    1 + ((LOW_FRICTION - friction).max(0.0) * 4.0 / LOW_FRICTION).round() as usize
+}
 
-        if y + 1 < grid.height && x > 0 && grid.get((x - 1, y + 1)) == NOTHING {
-            options.push((x - 1, y + 1));
+// The nearest cell down and to one side, at most `reach` cells away, that a powder at (x, y)
+// could slide into without passing through anything
+fn slide_target(
+    grid: &Grid,
+    x: usize,
+    y: usize,
+    direction: isize,
+    reach: usize,
+) -> Option<Vector2> {
+    grid.below((x, y))?;
+    for distance in 1..=reach as isize {
+        let side = grid.offset((x, y), grid.gravity.relative((direction * distance, 0)))?;
+        let side_below = grid.offset((x, y), grid.gravity.relative((direction * distance, 1)))?;
+        if *grid.get(side_below) == NOTHING {
+            return Some(side_below);
         }
+        if *grid.get(side) != NOTHING {
+            return None;
+        }
+    }
+    None
+}
+
+// Absorbent cells soak up liquid next to them until they are saturated. They dry out slowly
+// on their own, and much faster next to something hot.
+pub fn step_moisture(grid: &mut Grid, x: usize, y: usize) {
+    let Some(cell) = grid.get_cell((x, y)) else {
+        return;
+    };
+    let absorbency = cell.element.absorbency;
+    let mut moisture = cell.moisture;
+    let mut heated = false;
+    let mut rng = thread_rng();
 
-        if y + 1 < grid.height && x + 1 < grid.width && grid.get((x + 1, y + 1)) == NOTHING {
-            options.push((x + 1, y + 1));
+    for offset in [(0, -1), (-1, 0), (1, 0), (0, 1)] {
+        let Some(pos) = grid.offset((x, y), offset) else {
+            continue;
+        };
+        let neighbor = grid.get(pos);
+        if grid.get_cell(pos).is_some_and(|cell| cell.is_hot()) {
+            heated = true;
+        } else if neighbor.element_type == ElementType::Liquid
+            && moisture + absorbency <= 1.0
+            && rng.gen::<f32>() < ABSORB_CHANCE
+        {
+            grid.set(pos, &NOTHING);
+            moisture += absorbency;
         }
+    }
+
+    let drying = if heated {
+        HEATED_DRYING_RATE
+    } else {
+        DRYING_RATE
+    };
+    if let Some(cell) = grid.get_cell_mut((x, y)) {
+        cell.moisture = (moisture - drying).max(0.0);
+    }
+}
 
-        if !options.is_empty() {
-            let random_index = rand::gen_range(0, options.len());
-            let (new_x, new_y) = options[random_index];
-            grid.move_element((x, y), (new_x, new_y));
+// Give the cell at (x, y) a chance to react with each of its neighbors. Returns whether it
+// reacted, in which case it has turned into something else and shouldn't move this tick.
+// Powders only react once they have come to rest.
+pub fn step_reactions(grid: &mut Grid, x: usize, y: usize) -> bool {
+    let Some(cell) = grid.get_cell((x, y)) else {
+        return false;
+    };
+    if cell.element.element_type == ElementType::MoveableSolid && cell.free_falling {
+        return false;
+    }
+    let reactions = cell.element.reactions;
+    let mut rng = thread_rng();
+    for offset in [(0, -1), (-1, 0), (1, 0), (0, 1)] {
+        let Some(neighbor) = grid.offset((x, y), offset) else {
+            continue;
+        };
+        for reaction in reactions {
+            if *grid.get(neighbor) == *reaction.with && rng.gen::<f32>() < reaction.chance {
+                grid.set((x, y), reaction.becomes);
+                grid.set(neighbor, reaction.with_becomes);
+                return true;
+            }
         }
     }
+    false
 }
 
-pub fn step_immoveable_solid(grid: &mut Grid, x: usize, y: usize) {
-    if y + 1 < grid.height && grid.get((x, y + 1)) == WATER {
-        grid.swap_elements((x, y), (x, y + 1));
+// Give a corrosive cell at (x, y) a chance to dissolve each of its neighbors, based on how
+// well they resist it. Every cell dissolved may use the corrosive cell up, turning it into
+// fumes. Returns whether it was used up.
+pub fn step_corrosion(grid: &mut Grid, x: usize, y: usize) -> bool {
+    let corrosiveness = grid.get((x, y)).corrosiveness;
+    let mut rng = thread_rng();
+    for offset in [(0, 1), (-1, 0), (1, 0), (0, -1)] {
+        let Some(pos) = grid.offset((x, y), offset) else {
+            continue;
+        };
+        let neighbor = grid.get(pos);
+        if matches!(
+            neighbor.element_type,
+            ElementType::Nothing | ElementType::Liquid | ElementType::Gas | ElementType::Fire
+        ) {
+            continue;
+        }
+        if rng.gen::<f32>() >= corrosiveness * (1.0 - neighbor.corrosion_resistance) {
+            continue;
+        }
+        grid.set(pos, &NOTHING);
+        if rng.gen::<f32>() < CORROSION_SPENT_CHANCE {
+            grid.set((x, y), &FUMES);
+            return true;
+        }
     }
+    false
 }
 
-pub fn step_gas(grid: &mut Grid, x: usize, y: usize, diffusion_rate: usize) {
-    if y > 0 {
-        let above = grid.get((x, y - 1));
-        if above == NOTHING {
-            grid.move_element((x, y), (x, y - 1));
-        } else {
-            // Attempt to disperse left or right
+// Boil the liquid at (x, y) if it is next to something hot, or condense the gas at (x, y)
+// if it is near the top of the world or touching a cold surface. Anything solid that isn't
+// hot counts as cold. Returns whether the cell changed.
+pub fn step_phase_change(grid: &mut Grid, x: usize, y: usize) -> bool {
+    let element = grid.get((x, y));
+    let mut heated = false;
+    let mut cooled = grid.depth((x, y)) < CONDENSATION_HEIGHT;
+    for offset in [(0, -1), (-1, 0), (1, 0), (0, 1)] {
+        let Some(neighbor) = grid
+            .offset((x, y), offset)
+            .and_then(|pos| grid.get_cell(pos))
+        else {
+            continue;
+        };
+        if neighbor.is_hot() {
+            heated = true;
+        } else if matches!(
+            neighbor.element.element_type,
+            ElementType::ImmovableSolid | ElementType::MoveableSolid | ElementType::Plant
+        ) {
+            cooled = true;
+        }
+    }
 
-            let direction = rand::gen_range(0, 2) * 2;
+    let mut rng = thread_rng();
+    let change = match (element.boils_into, element.condenses_into) {
+        (Some(vapor), _) if heated && rng.gen::<f32>() < BOIL_CHANCE => vapor,
+        (_, Some(condensed)) if cooled && !heated && rng.gen::<f32>() < CONDENSE_CHANCE => {
+            condensed
+        }
+        _ => return false,
+    };
+    grid.set((x, y), change);
+    true
+}
 
-            for i in 1..=diffusion_rate {
-                let new_x = (x as usize + direction * i as usize) as usize;
+// Accelerate the cell at (x, y) under gravity and move it along its path until it hits
+// something. Returns where the cell ended up, or None if there was no room to fall into.
+// The cell keeps its velocity either way, so whatever it lands on next tick can see how
+// hard it hit.
+fn fall(grid: &mut Grid, x: usize, y: usize) -> Option<Vector2> {
+    let gravity = grid.gravity;
+    if gravity.is_zero_g() {
+        return None;
+    }
+    let below = grid.below((x, y))?;
+    if *grid.get(below) != NOTHING {
+        return None;
+    }
+    let (across, down) = gravity.to_relative(grid.get_velocity((x, y)));
+    let down = (down + GRAVITY * gravity.strength).min(TERMINAL_VELOCITY);
+    let offset = gravity.relative((across.round() as isize, down.round().max(1.0) as isize));
+    let mut end = grid.last_free_along((x, y), offset, |element| *element == NOTHING);
+    if end == (x, y) {
+        // Drifting sideways is blocked, but straight down is free
+        end = below;
+    }
+    grid.move_element((x, y), end);
+    grid.set_velocity(end, gravity.from_relative((across, down)));
+    Some(end)
+}
 
-                if new_x < grid.width {
-                    if thread_rng().gen_range(0..100) < diffusion_rate * 10 {
-                        let target = grid.get((new_x, y));
+// Gases random-walk in all four directions, moving up to `diffusion_rate` cells at a time
+// through empty space. On top of that, gases lighter than their surroundings rise and
+// heavier ones sink, more eagerly the bigger the difference in density. Empty space counts
+// as having the ambient density.
+pub fn step_gas(grid: &mut Grid, x: usize, y: usize, diffusion_rate: usize) {
+    let mut rng = thread_rng();
+    let density = grid.get((x, y)).density;
 
-                        if target == NOTHING {
-                            grid.move_element((x, y), (new_x, y));
-                            break;
-                        }
-                    }
+    // Without gravity there is nothing to be buoyant against
+    let buoyancy = if grid.gravity.is_zero_g() {
+        0.0
+    } else {
+        (AMBIENT_DENSITY - density).clamp(-1.0, 1.0)
+    };
+    if rng.gen::<f32>() < buoyancy.abs() {
+        let target = if buoyancy > 0.0 {
+            grid.above((x, y))
+        } else {
+            grid.below((x, y))
+        };
+        if let Some(target) = target {
+            if let Some(other_density) = gas_density(grid.get(target)) {
+                if (buoyancy > 0.0 && other_density > density)
+                    || (buoyancy < 0.0 && other_density < density)
+                {
+                    grid.swap_elements((x, y), target);
+                    return;
                 }
             }
         }
     }
+
+    if let Some(target) = blown_target(grid, x, y, GAS_WIND_RESPONSE) {
+        grid.move_element((x, y), target);
+        return;
+    }
+
+    let (dx, dy) = [(-1, 0), (1, 0), (0, -1), (0, 1)][rng.gen_range(0..4)];
+    let distance = rng.gen_range(1..=diffusion_rate.max(1)) as isize;
+    let offset = (dx * distance, dy * distance);
+    let end = grid.last_free_along((x, y), offset, |element| *element == NOTHING);
+    if end != (x, y) {
+        grid.move_element((x, y), end);
+    }
 }
 
-// This implementation looks pretty good, but is very poor for a couple reasons.
-// 2. We generate a new random number every single time instead of just using a preexisting pseudo-random number, like the
-//      frame count.
-// 3. The dispersion rate is buggy asf but does finally work.
-pub fn step_liquid(grid: &mut Grid, x: usize, y: usize, dispersion_rate: usize) {
-    // Check if the water can fall down
-    // If it can, move the water down
-    // Otherwise, attempt to disperse left or right
-    if y < grid.height - 1 {
-        let below = grid.get((x, y + 1));
-        if below == NOTHING {
-            grid.move_element((x, y), (x, y + 1));
-        } else {
-            // Attempt to disperse left or right
+// The empty cell next to (x, y) that the wind blows the cell there into this tick, if any
+fn blown_target(grid: &Grid, x: usize, y: usize, response: f32) -> Option<Vector2> {
+    let offset = wind_push(grid, (x, y), response)?;
+    let target = grid.offset((x, y), offset)?;
+    (*grid.get(target) == NOTHING).then_some(target)
+}
 
-            let direction = rand::gen_range(0, 2) * 2 - 1;
+// The density a gas has to push against to move into an element, or None if it can't
+// move into it at all
+fn gas_density(element: &Element) -> Option<f32> {
+    match element.element_type {
+        ElementType::Nothing => Some(AMBIENT_DENSITY),
+        ElementType::Gas => Some(element.density),
+        _ => None,
+    }
+}
+
+// Liquids fall like powders, but once they land their falling speed is turned into
+// sideways flow. A liquid keeps flowing in the same direction until it is blocked, and
+// always flows at least `dispersion_rate` cells per tick. Viscous liquids only flow some
+// of the time.
+pub fn step_liquid(grid: &mut Grid, x: usize, y: usize, dispersion_rate: usize) {
+    if let Some(end) = fall(grid, x, y) {
+        grid.set_free_falling(end, true);
+        return;
+    }
+    // Landed, so from now on the cell is part of whatever body of liquid it is touching
+    grid.set_free_falling((x, y), false);
+    let gravity = grid.gravity;
+    if gravity.is_zero_g() {
+        // Nothing pulls the liquid flat, so it just floats where it is
+        return;
+    }
 
-            for i in 1..=dispersion_rate {
-                let new_x = (x as i32 + direction * i as i32) as usize;
+    if thread_rng().gen::<f32>() < grid.get((x, y)).viscosity {
+        return;
+    }
 
-                if new_x < grid.width {
-                    let target = grid.get((new_x, y));
+    let (across, down) = gravity.to_relative(grid.get_velocity((x, y)));
+    let direction = if across != 0.0 {
+        across.signum()
+    } else {
+        (rand::gen_range(0, 2) * 2 - 1) as f32
+    };
+
+    // Hitting the ground hard enough throws some of the liquid back up as a splash
+    if down >= SPLASH_VELOCITY && thread_rng().gen_bool(SPLASH_CHANCE) {
+        let splash = gravity.from_relative((direction * down * 0.4, -down * 0.6));
+        grid.eject((x, y), splash);
+        return;
+    }
 
-                    if target == NOTHING {
-                        grid.move_element((x, y), (new_x, y));
-                        break;
-                    }
-                }
-            }
-        }
+    let speed = (across.abs() + down).max(dispersion_rate as f32);
+    let offset = gravity.relative(((direction * speed.round()) as isize, 0));
+    let end = grid.last_free_along((x, y), offset, |element| *element == NOTHING);
+    if end == (x, y) {
+        // Blocked, so pick a new direction next tick
+        grid.set_velocity((x, y), (0.0, 0.0));
+        return;
     }
+    grid.move_element((x, y), end);
+    let flow = direction * (speed * FLOW_DAMPING).max(dispersion_rate as f32);
+    grid.set_velocity(end, gravity.from_relative((flow, 0.0)));
 }
 
 pub fn step_fire(grid: &mut Grid, x: usize, y: usize) {
     let mut rng = thread_rng();
     let upward_chance = 0.7;
 
-    // Check if the pixel above is empty and within grid bounds
-    if y > 0 && grid.get((x, y - 1)) == NOTHING {
+    ignite_neighbors(grid, x, y);
+
+    if let Some(target) = blown_target(grid, x, y, GAS_WIND_RESPONSE) {
+        grid.move_element((x, y), target);
+        return;
+    }
+
+    // Rise into the empty cell above, unless there is no gravity to rise against
+    let above = grid
+        .above((x, y))
+        .filter(|&above| *grid.get(above) == NOTHING && !grid.gravity.is_zero_g());
+    if let Some(above) = above {
         // Move upward with a chance based on upward_chance
         if rng.gen::<f32>() < upward_chance {
-            grid.move_element((x, y), (x, y - 1));
+            grid.move_element((x, y), above);
             return; // Fire moves only once per step
         }
     }
 
     // If no upward movement occurred, the fire drifts randomly
     let drift_direction = rng.gen_range(-1..=1); // -1 for left, 0 for no drift, 1 for right
-    let new_x = (x as i32 + drift_direction) as usize;
+    let drift = grid.gravity.relative((drift_direction, 0));
 
     // Check if the new position is within grid bounds and empty
-    if new_x < grid.width && grid.get((new_x, y)) == NOTHING {
-        grid.move_element((x, y), (new_x, y));
-    } else {
-        // If no movement is possible, the fire dies out, turning into NOTHING
-        grid.set((x, y), NOTHING);
+    // If no movement is possible the fire stays put until it burns out
+    if let Some(target) = grid.offset((x, y), drift) {
+        if *grid.get(target) == NOTHING {
+            grid.move_element((x, y), target);
+        }
     }
 }
 
-pub fn step_pixel_generator(grid: &mut Grid, x: usize, y: usize) {
-    // Check if there is air below
-    if y + 1 < grid.height && grid.get((x, y + 1)) == NOTHING {
-        grid.set((x, y + 1), WATER);
+// Plant cells draw water from anything wetter below or beside them, so water soaked up by the
+// roots rises through the stem to the growing tip. The tip grows whenever it has water to spare.
+pub fn step_plant(grid: &mut Grid, x: usize, y: usize) {
+    let Some(cell) = grid.get_cell((x, y)) else {
+        return;
+    };
+    let mut moisture = cell.moisture;
+    let growth = cell.element.growth;
+    // Only from below and beside, so water rises through the plant. Diagonals count too,
+    // since plants grow diagonally.
+    for offset in [(0, 1), (-1, 1), (1, 1), (-1, 0), (1, 0)] {
+        let Some(neighbor) = grid.offset((x, y), grid.gravity.relative(offset)) else {
+            continue;
+        };
+        if let Some(neighbor) = grid.get_cell_mut(neighbor) {
+            if neighbor.moisture > moisture {
+                let drawn = (neighbor.moisture - moisture) * WICKING_RATE;
+                neighbor.moisture -= drawn;
+                moisture += drawn;
+            }
+        }
+    }
+    if let Some(cell) = grid.get_cell_mut((x, y)) {
+        cell.moisture = moisture.min(1.0);
     }
-}
 
-pub fn step_pixel_destroyer(grid: &mut Grid, x: usize, y: usize) {
-    // kill all neighbors
-    for dx in -1..=1 {
-        for dy in -1..=1 {
-            // Skip the current cell
-            if dx == 0 && dy == 0 {
-                continue;
+    let Some(growth) = growth else {
+        return;
+    };
+    let mut rng = thread_rng();
+    if moisture < GROWTH_COST || rng.gen::<f32>() >= growth.rate {
+        return;
+    }
+    let Some(target) = choose_growth_target(grid, x, y, growth.clings) else {
+        return;
+    };
+    if *grid.get(target) == WATER {
+        // Growing into water drinks it
+        moisture += grid.get((x, y)).absorbency;
+    }
+    grid.move_element((x, y), target);
+    grid.set((x, y), growth.stem);
+    if let Some(cell) = grid.get_cell_mut(target) {
+        cell.moisture = (moisture - GROWTH_COST).min(1.0);
+    }
+
+    if rng.gen::<f32>() < growth.leaf_chance {
+        let side = if rng.gen_bool(0.5) { -1 } else { 1 };
+        if let Some(leaf) = grid.offset((x, y), grid.gravity.relative((side, 0))) {
+            if *grid.get(leaf) == NOTHING {
+                grid.set(leaf, growth.leaf);
             }
+        }
+    }
+}
 
-            let nx = x as i32 + dx;
-            let ny = y as i32 + dy;
+// Pick where the plant tip at (x, y) grows next, at random but favoring upward directions
+// with plenty of open space around them. Vines can only grow into cells that touch
+// something solid other than the plant itself.
+fn choose_growth_target(grid: &Grid, x: usize, y: usize, clings: bool) -> Option<Vector2> {
+    let directions: &[((isize, isize), f32)] = if clings {
+        &[
+            ((0, -1), 3.0),
+            ((-1, -1), 2.0),
+            ((1, -1), 2.0),
+            ((-1, 0), 2.0),
+            ((1, 0), 2.0),
+            ((-1, 1), 1.0),
+            ((1, 1), 1.0),
+            ((0, 1), 1.0),
+        ]
+    } else {
+        &[((0, -1), 3.0), ((-1, -1), 1.0), ((1, -1), 1.0)]
+    };
+
+    let mut candidates = Vec::new();
+    for &(offset, weight) in directions {
+        let Some(target) = grid.offset((x, y), grid.gravity.relative(offset)) else {
+            continue;
+        };
+        if *grid.get(target) != NOTHING && *grid.get(target) != WATER {
+            continue;
+        }
+        if clings && !touches_surface(grid, target) {
+            continue;
+        }
+        candidates.push((target, weight * (1 + open_space(grid, target)) as f32));
+    }
 
-            if nx >= 0 && nx < grid.width as i32 && ny >= 0 && ny < grid.height as i32 {
-                // Check if the neighboring cell is not a pixel destroyer
-                if grid.get((nx as usize, ny as usize)) != DRAIN {
-                    grid.set((nx as usize, ny as usize), NOTHING);
-                }
+    let total: f32 = candidates.iter().map(|(_, weight)| weight).sum();
+    let mut roll = thread_rng().gen::<f32>() * total;
+    for (target, weight) in candidates {
+        if roll < weight {
+            return Some(target);
+        }
+        roll -= weight;
+    }
+    None
+}
+
+// Number of empty cells within a few cells of the given position
+fn open_space(grid: &Grid, pos: Vector2) -> usize {
+    let mut count = 0;
+    for dy in -3..=3 {
+        for dx in -3..=3 {
+            let neighbor = grid.offset(pos, (dx, dy));
+            if neighbor.is_some_and(|neighbor| *grid.get(neighbor) == NOTHING) {
+                count += 1;
             }
         }
     }
+    count
 }
 
-// Maze is a Life-like cellular automaton in which cells survive from one generation to the next if they have at least 1 and at most 5 neighbours. Cells are born if they have exactly 3 neighbours. This resembles Conway's Game of Life in some ways, but it is rather more difficult for cells to die off, and random starting patterns tend to evolve into complex growing maze-like structures with well-defined walls outlining corridors.
-// https://conwaylife.com/wiki/OCA:Maze
-pub fn step_maze(grid: &mut Grid, x: usize, y: usize) {
-    let mut maze_neighbors = 0;
+// Whether the given position is next to a solid that isn't part of a plant
+fn touches_surface(grid: &Grid, pos: Vector2) -> bool {
+    [(0, -1), (-1, 0), (1, 0), (0, 1)]
+        .into_iter()
+        .filter_map(|offset| grid.offset(pos, offset))
+        .any(|neighbor| {
+            matches!(
+                grid.get(neighbor).element_type,
+                ElementType::ImmovableSolid | ElementType::MoveableSolid
+            )
+        })
+}
 
-    // Check all neighboring cells
+// Give each flammable neighbor of (x, y) a chance to catch fire, based on its flammability
+pub fn ignite_neighbors(grid: &mut Grid, x: usize, y: usize) {
+    let mut rng = thread_rng();
     for dx in -1..=1 {
         for dy in -1..=1 {
-            // Skip the current cell
             if dx == 0 && dy == 0 {
                 continue;
             }
 
-            let nx = x as i32 + dx;
-            let ny = y as i32 + dy;
+            if let Some(neighbor) = grid.offset((x, y), (dx, dy)) {
+                let flammability = grid.get(neighbor).flammability;
+                if flammability > 0.0 && rng.gen::<f32>() < flammability {
+                    ignite(grid, neighbor);
+                }
+            }
+        }
+    }
+}
+
+// Set the cell at the given position alight. Explosives blow up instead of burning.
+pub fn ignite(grid: &mut Grid, pos: Vector2) {
+    let explosion_radius = grid.get(pos).explosion_radius;
+    if explosion_radius > 0 {
+        explode(grid, pos, explosion_radius);
+    } else {
+        grid.set(pos, &FIRE);
+    }
+}
 
-            // Check if the neighboring cell is within the grid bounds
-            if nx >= 0 && nx < grid.width as i32 && ny >= 0 && ny < grid.height as i32 {
-                let neighbor = grid.get((nx as usize, ny as usize));
+pub fn step_pixel_generator(grid: &mut Grid, x: usize, y: usize) {
+    let generator = grid.get((x, y));
+    let powered = grid.get_cell((x, y)).is_some_and(|cell| cell.is_powered());
+    if generator.powered == Some(PoweredEffect::Switch) && !powered {
+        return;
+    }
+    let picked_up = grid.get_cell((x, y)).and_then(|cell| cell.emits);
+    let Some(emits) = generator.emits.or(picked_up) else {
+        // Nothing to emit yet, so clone the first element that touches us
+        if let Some(element) = first_neighbor_to_clone(grid, x, y) {
+            if let Some(cell) = grid.get_cell_mut((x, y)) {
+                cell.emits = Some(element);
+            }
+        }
+        return;
+    };
 
-                // Check if the neighboring cell is not a maze cell
-                if neighbor != MAZE {
-                    // Check if the neighboring cell has 3 neighbors
-                    let neighbor_neighbors = count_maze_neighbors(grid, nx as usize, ny as usize);
+    if thread_rng().gen::<f32>() >= generator.emit_rate {
+        return;
+    }
 
-                    // Set the neighboring cell to maze if it has 3 neighbors
-                    if neighbor_neighbors == 3 {
-                        grid.set((nx as usize, ny as usize), MAZE);
-                    }
-                }
+    let (dx, dy) = generator.emit_direction;
+    if (dx, dy) == (0, 0) {
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                emit(grid, x, y, (dx, dy), emits);
             }
         }
+    } else {
+        emit(grid, x, y, (dx, dy), emits);
     }
+}
 
-    // Check the current cell
-    if grid.get((x, y)) == MAZE {
-        let current_neighbors = count_maze_neighbors(grid, x, y);
+// Place the element next to (x, y) in the given direction if there is room, already
+// moving away from the generator
+fn emit(grid: &mut Grid, x: usize, y: usize, direction: (isize, isize), element: &'static Element) {
+    if direction == (0, 0) {
+        return;
+    }
+    let Some(target) = grid.offset((x, y), direction) else {
+        return;
+    };
+    if *grid.get(target) == NOTHING {
+        grid.set(target, element);
+        grid.set_velocity(target, (direction.0 as f32, direction.1 as f32));
+    }
+}
 
-        // Set the current cell to nothing if it has less than 1 or more than 5 neighbors
-        if current_neighbors < 1 || current_neighbors > 5 {
-            grid.set((x, y), NOTHING);
+fn first_neighbor_to_clone(grid: &Grid, x: usize, y: usize) -> Option<&'static Element> {
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            let Some(pos) = grid.offset((x, y), (dx, dy)) else {
+                continue;
+            };
+            let neighbor = grid.get(pos);
+            if !matches!(
+                neighbor.element_type,
+                ElementType::Nothing | ElementType::PixelGenerator | ElementType::PixelDestroyer
+            ) {
+                return Some(neighbor);
+            }
         }
     }
+    None
 }
 
-fn count_maze_neighbors(grid: &Grid, x: usize, y: usize) -> usize {
-    let mut neighbor_neighbors = 0;
+pub fn step_pixel_destroyer(grid: &mut Grid, x: usize, y: usize) {
+    let filter = grid.get((x, y)).drain_filter;
 
+    // kill all neighbors the drain accepts
     for dx in -1..=1 {
         for dy in -1..=1 {
-            // Skip the current cell and the neighboring cell
-            if (dx == 0 && dy == 0) || (dx == 0 && dy == 0) {
+            // Skip the current cell
+            if dx == 0 && dy == 0 {
                 continue;
             }
 
-            let nx = x as i32 + dx;
-            let ny = y as i32 + dy;
+            if let Some(pos) = grid.offset((x, y), (dx, dy)) {
+                let neighbor = grid.get(pos);
+                if filter.accepts(neighbor) {
+                    grid.set(pos, &NOTHING);
+                    grid.record_drained(neighbor);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::AIR;
+
+    #[test]
+    fn gas_cloud_spreads_symmetrically() {
+        // Air is lighter than empty space, so start low enough that it can't reach the top
+        let mut grid = Grid::new(120, 160);
+        let center = (grid.width / 2, 120);
+        for x in center.0 - 10..center.0 + 10 {
+            for y in center.1 - 10..center.1 + 10 {
+                grid.set((x, y), &AIR);
+            }
+        }
 
-            // Check if the neighbor of the current cell is within the grid bounds
-            if nx >= 0 && nx < grid.width as i32 && ny >= 0 && ny < grid.height as i32 {
-                let neighbor = grid.get((nx as usize, ny as usize));
+        for _ in 0..200 {
+            grid.update();
+        }
 
-                // Check if the neighbor of the current cell is a maze cell
-                if neighbor == MAZE {
-                    neighbor_neighbors += 1;
+        let mut count: usize = 0;
+        let mut left: usize = 0;
+        let mut sum_x = 0;
+        let mut sum_y = 0;
+        let (mut min_x, mut max_x) = (usize::MAX, 0);
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                if *grid.get((x, y)) == AIR {
+                    count += 1;
+                    if x < center.0 {
+                        left += 1;
+                    }
+                    sum_x += x;
+                    sum_y += y;
+                    min_x = min_x.min(x);
+                    max_x = max_x.max(x);
                 }
             }
         }
-    }
 
-    neighbor_neighbors
+        assert_eq!(count, 400);
+        let mean_x = sum_x as f32 / count as f32;
+        assert!(
+            (mean_x - center.0 as f32 + 0.5).abs() < 5.0,
+            "cloud drifted to x = {}",
+            mean_x
+        );
+        // Being lighter than empty space, the cloud rises as a whole
+        let mean_y = sum_y as f32 / count as f32;
+        assert!(mean_y < center.1 as f32 - 5.0, "cloud didn't rise");
+        // Both edges of the cloud have spread well beyond where they started
+        assert!(min_x < center.0 - 20, "cloud didn't spread left");
+        assert!(max_x > center.0 + 20, "cloud didn't spread right");
+        let right = count - left;
+        assert!(
+            left.abs_diff(right) < count * 15 / 100,
+            "{} cells went left and {} right",
+            left,
+            right
+        );
+    }
 }
@@ -1,46 +1,245 @@
 use macroquad::prelude::*;
 
+use crate::automaton::AutomatonRule;
 use crate::element_type::{
-    step_fire, step_gas, step_immoveable_solid, step_liquid, step_maze, step_moveable_solid,
-    step_pixel_destroyer, step_pixel_generator, ElementType,
+    ignite_neighbors, step_corrosion, step_fire, step_gas, step_liquid, step_moisture,
+    step_moveable_solid, step_phase_change, step_pixel_destroyer, step_pixel_generator, step_plant,
+    step_reactions, ElementType, AMBIENT_DENSITY,
 };
 use crate::grid::Grid;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy)]
 
 pub struct Element {
     pub element_type: ElementType,
     pub color: Option<Color>,
     pub color_variance: f32,
     pub name: &'static str,
+    pub lifetime: Option<Lifetime>,
+    // Chance per tick of catching fire while next to a fire
+    pub flammability: f32,
+    // Chance of a cell surviving an explosion that reaches it
+    pub hardness: f32,
+    // Radius of the blast when the element is ignited, or 0 if it just burns
+    pub explosion_radius: usize,
+    // Pixel generators: the element emitted, the direction it is emitted in and the chance
+    // per tick of emitting. A direction of (0, 0) emits into every free neighbor. Generators
+    // that don't emit anything take on the first element that touches them.
+    pub emits: Option<&'static Element>,
+    pub emit_direction: (isize, isize),
+    pub emit_rate: f32,
+    // Pixel destroyers: which neighbors get consumed
+    pub drain_filter: DrainFilter,
+    // Automata: the Life-like rule the element follows
+    pub rule: Option<AutomatonRule>,
+    // Liquids: how many cells sideways they flow per tick, and the chance per tick of not
+    // flowing at all
+    pub dispersion_rate: usize,
+    pub viscosity: f32,
+    // Gases: the furthest they move in one step of their random walk, which goes up, down,
+    // left or right
+    pub diffusion_rate: usize,
+    // Gases: density relative to the ambient density of empty space, which decides whether
+    // they rise or sink. Powders lighter than the ambient density get blown about by the
+    // wind. Rigid bodies: mass per cell, which decides whether they float in liquids of the
+    // given density.
+    pub density: f32,
+    // Powders: chance per tick of a sliding grain coming to rest. Higher friction makes
+    // steeper piles, and very low friction lets grains spread out almost flat.
+    pub friction: f32,
+    // Moisture gained from each cell of liquid soaked up, or 0 if the element doesn't absorb
+    pub absorbency: f32,
+    // Powders that hold their shape instead of sliding once they are wet enough
+    pub moldable: bool,
+    // Hot elements dry out wet cells next to them and set flammable neighbors alight
+    pub hot: bool,
+    // Radius in cells of the light the element gives off, or 0 if it doesn't glow
+    pub glow: f32,
+    // Liquids: what they boil into next to something hot. Gases: what they condense into
+    // near the top of the world or against a cold surface.
+    pub boils_into: Option<&'static Element>,
+    pub condenses_into: Option<&'static Element>,
+    // Structural solids: what the element breaks into when it loses its support, and how
+    // many cells of overhang it can hold up. Elements without debris never collapse.
+    pub debris: Option<&'static Element>,
+    pub strength: usize,
+    // Electricity: whether charge passes through the element, whether it charges the
+    // conductors touching it, and what it does while charge is passing through it
+    pub conductive: bool,
+    pub power_source: bool,
+    pub powered: Option<PoweredEffect>,
+    // What the element turns into when it touches certain other elements
+    pub reactions: &'static [Reaction],
+    // Corrosive liquids: chance per tick of eating into each neighbor, and how much each
+    // element resists them, from 0 (dissolves readily) to 1 (immune). Liquids and gases are
+    // never dissolved.
+    pub corrosiveness: f32,
+    pub corrosion_resistance: f32,
+    // Plants: how the growing tip extends the plant, or None for the rest of the plant
+    pub growth: Option<Growth>,
+}
+
+// How long cells of an element live for, and what they turn into once they expire.
+// Each cell picks a random lifetime between `min` and `max` ticks when it is created.
+#[derive(Clone, Copy)]
+pub struct Lifetime {
+    pub min: u32,
+    pub max: u32,
+    pub decays_into: &'static Element,
+}
+
+// When a cell touches a cell of `with`, it has `chance` per tick of turning into `becomes`
+// while the cell it touched turns into `with_becomes`
+#[derive(Clone, Copy)]
+pub struct Reaction {
+    pub with: &'static Element,
+    pub chance: f32,
+    pub becomes: &'static Element,
+    pub with_becomes: &'static Element,
+}
+
+// How the growing tip of a plant extends it. Each time the tip grows it moves into a free
+// cell, leaving `stem` behind and now and then putting out a `leaf` beside it. Growing uses
+// up the water the tip has drawn in.
+#[derive(Clone, Copy)]
+pub struct Growth {
+    pub stem: &'static Element,
+    pub leaf: &'static Element,
+    // Chance per tick of growing while the tip has water, and of putting out a leaf
+    pub rate: f32,
+    pub leaf_chance: f32,
+    // Vines grow in any direction as long as they stay against a surface, while other
+    // plants grow upward towards open space
+    pub clings: bool,
+}
+
+// What a conductive component does while it is powered
+#[derive(Clone, Copy, PartialEq)]
+pub enum PoweredEffect {
+    // Counts as hot, drying out and setting alight whatever touches it like fire does
+    Heat,
+    // Sets every flammable neighbor alight each time a charge arrives
+    Ignite,
+    // Generators only emit while powered
+    Switch,
+}
+
+// Which elements a pixel destroyer consumes
+#[derive(Clone, Copy)]
+pub enum DrainFilter {
+    // Anything that can move: powders, liquids, gases and fire
+    Loose,
+    Liquids,
+    Gases,
+    Only(&'static [&'static Element]),
+}
+
+impl DrainFilter {
+    pub fn accepts(&self, element: &Element) -> bool {
+        match self {
+            DrainFilter::Loose => matches!(
+                element.element_type,
+                ElementType::MoveableSolid
+                    | ElementType::Liquid
+                    | ElementType::Gas
+                    | ElementType::Fire
+            ),
+            DrainFilter::Liquids => element.element_type == ElementType::Liquid,
+            DrainFilter::Gases => element.element_type == ElementType::Gas,
+            DrainFilter::Only(elements) => elements.contains(&element),
+        }
+    }
 }
 
 impl Element {
+    // Base values for element definitions, so each one only needs to spell out what it changes.
+    pub const DEFAULT: Element = Element {
+        element_type: ElementType::Nothing,
+        color: None,
+        color_variance: 0.0,
+        name: "",
+        lifetime: None,
+        flammability: 0.0,
+        hardness: 0.0,
+        explosion_radius: 0,
+        emits: None,
+        emit_direction: (0, 1),
+        emit_rate: 1.0,
+        drain_filter: DrainFilter::Loose,
+        rule: None,
+        dispersion_rate: 4,
+        viscosity: 0.0,
+        diffusion_rate: 1,
+        density: AMBIENT_DENSITY,
+        friction: 0.3,
+        absorbency: 0.0,
+        moldable: false,
+        hot: false,
+        glow: 0.0,
+        boils_into: None,
+        condenses_into: None,
+        debris: None,
+        strength: 0,
+        conductive: false,
+        power_source: false,
+        powered: None,
+        reactions: &[],
+        corrosiveness: 0.0,
+        corrosion_resistance: 0.0,
+        growth: None,
+    };
+
     pub fn step(&self, grid: &mut Grid, x: usize, y: usize) {
         if !grid.is_within_bounds((x, y)) {
             return;
         }
+        if !self.reactions.is_empty() && step_reactions(grid, x, y) {
+            return;
+        }
+        if self.corrosiveness > 0.0 && step_corrosion(grid, x, y) {
+            return;
+        }
+        if (self.boils_into.is_some() || self.condenses_into.is_some())
+            && step_phase_change(grid, x, y)
+        {
+            return;
+        }
+        if self.absorbency > 0.0 {
+            step_moisture(grid, x, y);
+        }
+        // Fire spreads itself as it moves
+        let hot = grid.get_cell((x, y)).is_some_and(|cell| cell.is_hot());
+        if hot && self.element_type != ElementType::Fire {
+            ignite_neighbors(grid, x, y);
+        }
         match self.element_type {
-            ElementType::ImmovableSolid => step_immoveable_solid(grid, x, y),
             ElementType::MoveableSolid => step_moveable_solid(grid, x, y),
-            ElementType::Liquid => step_liquid(grid, x, y, 4),
-            ElementType::Gas => step_gas(grid, x, y, 1),
+            ElementType::Liquid => step_liquid(grid, x, y, self.dispersion_rate),
+            ElementType::Gas => step_gas(grid, x, y, self.diffusion_rate),
             ElementType::PixelGenerator => step_pixel_generator(grid, x, y),
             ElementType::PixelDestroyer => step_pixel_destroyer(grid, x, y),
-            ElementType::Maze => step_maze(grid, x, y),
             ElementType::Fire => step_fire(grid, x, y),
+            ElementType::Plant => step_plant(grid, x, y),
             _ => {}
         }
     }
     pub fn to_string(&self) -> &str {
-        return self.name;
+        self.name
     }
     pub fn get_color(&self) -> Option<Color> {
-        return self.color;
+        self.color
     }
 
     pub fn get_element_type(&self) -> ElementType {
-        return self.element_type;
+        self.element_type
+    }
+}
+
+// Elements are uniquely identified by their name. Comparing the other fields is
+// unnecessary, and would recurse forever for elements that decay into each other.
+impl PartialEq for Element {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
     }
 }
 
@@ -49,6 +248,9 @@ pub static AIR: Element = Element {
     color: Some(SKYBLUE),
     color_variance: 0.05,
     name: "Air",
+    diffusion_rate: 2,
+    density: 0.8,
+    ..Element::DEFAULT
 };
 
 pub static SAND: Element = Element {
@@ -56,6 +258,9 @@ pub static SAND: Element = Element {
     color: Some(GOLD),
     color_variance: 0.07,
     name: "Sand",
+    absorbency: 0.5,
+    friction: 0.25,
+    ..Element::DEFAULT
 };
 
 pub static WATER: Element = Element {
@@ -63,6 +268,10 @@ pub static WATER: Element = Element {
     color: Some(BLUE),
     color_variance: 0.15,
     name: "Water",
+    dispersion_rate: 5,
+    boils_into: Some(&STEAM),
+    conductive: true,
+    ..Element::DEFAULT
 };
 
 pub static STONE: Element = Element {
@@ -70,6 +279,12 @@ pub static STONE: Element = Element {
     color: Some(DARKGRAY),
     color_variance: 0.0,
     name: "Stone",
+    strength: 6,
+    debris: Some(&GRAVEL),
+    hardness: 0.8,
+    density: 2.5,
+    corrosion_resistance: 0.8,
+    ..Element::DEFAULT
 };
 
 pub static FAUCET: Element = Element {
@@ -77,6 +292,10 @@ pub static FAUCET: Element = Element {
     color: Some(WHITE),
     color_variance: 0.0,
     name: "Faucet",
+    emits: Some(&WATER),
+    hardness: 1.0,
+    corrosion_resistance: 1.0,
+    ..Element::DEFAULT
 };
 
 pub static CLAY: Element = Element {
@@ -84,6 +303,12 @@ pub static CLAY: Element = Element {
     color: Some(BROWN),
     color_variance: 0.0,
     name: "Clay",
+    moldable: true,
+    absorbency: 0.34,
+    friction: 0.6,
+    hardness: 0.3,
+    corrosion_resistance: 0.5,
+    ..Element::DEFAULT
 };
 
 pub static NOTHING: Element = Element {
@@ -91,13 +316,18 @@ pub static NOTHING: Element = Element {
     color: None,
     color_variance: 0.0,
     name: "Nothing",
+    ..Element::DEFAULT
 };
 
+// Maze is a Life-like cellular automaton in which cells survive from one generation to the next if they have at least 1 and at most 5 neighbours. Cells are born if they have exactly 3 neighbours. This resembles Conway's Game of Life in some ways, but it is rather more difficult for cells to die off, and random starting patterns tend to evolve into complex growing maze-like structures with well-defined walls outlining corridors.
+// https://conwaylife.com/wiki/OCA:Maze
 pub static MAZE: Element = Element {
-    element_type: ElementType::Maze,
+    element_type: ElementType::Automaton,
     color: Some(WHITE),
     color_variance: 0.0,
     name: "Maze",
+    rule: Some(AutomatonRule::parse("B3/S12345")),
+    ..Element::DEFAULT
 };
 
 pub static FIRE: Element = Element {
@@ -105,6 +335,13 @@ pub static FIRE: Element = Element {
     color: Some(RED),
     color_variance: 0.0,
     name: "Fire",
+    hot: true,
+    lifetime: Some(Lifetime {
+        min: 20,
+        max: 80,
+        decays_into: &SMOKE,
+    }),
+    ..Element::DEFAULT
 };
 
 pub static DRAIN: Element = Element {
@@ -112,4 +349,521 @@ pub static DRAIN: Element = Element {
     color: Some(DARKGRAY),
     color_variance: 0.0,
     name: "Drain",
+    hardness: 1.0,
+    corrosion_resistance: 1.0,
+    ..Element::DEFAULT
+};
+
+pub static SMOKE: Element = Element {
+    element_type: ElementType::Gas,
+    color: Some(GRAY),
+    color_variance: 0.1,
+    name: "Smoke",
+    density: 0.6,
+    lifetime: Some(Lifetime {
+        min: 60,
+        max: 240,
+        decays_into: &NOTHING,
+    }),
+    ..Element::DEFAULT
+};
+
+pub static EMBER: Element = Element {
+    element_type: ElementType::MoveableSolid,
+    color: Some(ORANGE),
+    color_variance: 0.2,
+    name: "Ember",
+    hot: true,
+    lifetime: Some(Lifetime {
+        min: 120,
+        max: 480,
+        decays_into: &ASH,
+    }),
+    density: 0.6,
+    ..Element::DEFAULT
+};
+
+pub static ASH: Element = Element {
+    element_type: ElementType::MoveableSolid,
+    color: Some(LIGHTGRAY),
+    color_variance: 0.05,
+    name: "Ash",
+    friction: 0.1,
+    density: 0.4,
+    ..Element::DEFAULT
+};
+
+pub static GUNPOWDER: Element = Element {
+    element_type: ElementType::MoveableSolid,
+    color: Some(Color::new(0.25, 0.25, 0.25, 1.0)),
+    color_variance: 0.1,
+    name: "Gunpowder",
+    flammability: 0.8,
+    explosion_radius: 4,
+    ..Element::DEFAULT
+};
+
+pub static TNT: Element = Element {
+    element_type: ElementType::ImmovableSolid,
+    color: Some(Color::new(0.8, 0.1, 0.1, 1.0)),
+    color_variance: 0.0,
+    name: "TNT",
+    flammability: 0.5,
+    explosion_radius: 12,
+    ..Element::DEFAULT
+};
+
+pub static SAND_SPOUT: Element = Element {
+    element_type: ElementType::PixelGenerator,
+    color: Some(BEIGE),
+    color_variance: 0.0,
+    name: "Sand spout",
+    hardness: 1.0,
+    corrosion_resistance: 1.0,
+    emits: Some(&SAND),
+    emit_rate: 0.5,
+    ..Element::DEFAULT
+};
+
+pub static GAS_VENT: Element = Element {
+    element_type: ElementType::PixelGenerator,
+    color: Some(LIGHTGRAY),
+    color_variance: 0.0,
+    name: "Gas vent",
+    hardness: 1.0,
+    corrosion_resistance: 1.0,
+    emits: Some(&AIR),
+    emit_direction: (0, -1),
+    emit_rate: 0.3,
+    ..Element::DEFAULT
+};
+
+pub static CLONE: Element = Element {
+    element_type: ElementType::PixelGenerator,
+    color: Some(YELLOW),
+    color_variance: 0.0,
+    name: "Clone",
+    hardness: 1.0,
+    corrosion_resistance: 1.0,
+    emit_direction: (0, 0),
+    emit_rate: 0.2,
+    ..Element::DEFAULT
+};
+
+pub static LIQUID_DRAIN: Element = Element {
+    element_type: ElementType::PixelDestroyer,
+    color: Some(DARKBLUE),
+    color_variance: 0.0,
+    name: "Liquid drain",
+    hardness: 1.0,
+    corrosion_resistance: 1.0,
+    drain_filter: DrainFilter::Liquids,
+    ..Element::DEFAULT
+};
+
+pub static GAS_DRAIN: Element = Element {
+    element_type: ElementType::PixelDestroyer,
+    color: Some(DARKPURPLE),
+    color_variance: 0.0,
+    name: "Gas drain",
+    hardness: 1.0,
+    corrosion_resistance: 1.0,
+    drain_filter: DrainFilter::Gases,
+    ..Element::DEFAULT
+};
+
+pub static WATER_DRAIN: Element = Element {
+    element_type: ElementType::PixelDestroyer,
+    color: Some(DARKGREEN),
+    color_variance: 0.0,
+    name: "Water drain",
+    hardness: 1.0,
+    corrosion_resistance: 1.0,
+    drain_filter: DrainFilter::Only(&[&WATER]),
+    ..Element::DEFAULT
+};
+
+// https://conwaylife.com/wiki/Conway%27s_Game_of_Life
+pub static LIFE: Element = Element {
+    element_type: ElementType::Automaton,
+    color: Some(LIME),
+    color_variance: 0.0,
+    name: "Life",
+    rule: Some(AutomatonRule::parse("B3/S23")),
+    ..Element::DEFAULT
+};
+
+// Like Life, but cells are also born with 6 neighbours, which gives it a replicator.
+// https://conwaylife.com/wiki/OCA:HighLife
+pub static HIGHLIFE: Element = Element {
+    element_type: ElementType::Automaton,
+    color: Some(SKYBLUE),
+    color_variance: 0.0,
+    name: "HighLife",
+    rule: Some(AutomatonRule::parse("B36/S23")),
+    ..Element::DEFAULT
+};
+
+// Symmetric under swapping live and dead cells, so patterns of "holes" behave like patterns of cells.
+// https://conwaylife.com/wiki/OCA:Day_%26_Night
+pub static DAY_AND_NIGHT: Element = Element {
+    element_type: ElementType::Automaton,
+    color: Some(PINK),
+    color_variance: 0.0,
+    name: "Day & Night",
+    rule: Some(AutomatonRule::parse("B3678/S34678")),
+    ..Element::DEFAULT
+};
+
+// Generations rule where every live cell dies after one generation, giving constantly moving sparks.
+// https://conwaylife.com/wiki/OCA:Brian%27s_Brain
+pub static BRIANS_BRAIN: Element = Element {
+    element_type: ElementType::Automaton,
+    color: Some(VIOLET),
+    color_variance: 0.0,
+    name: "Brian's Brain",
+    rule: Some(AutomatonRule::parse("B2/S/C3")),
+    ..Element::DEFAULT
+};
+
+// Generations rule that grows chaotic structures with long fading trails.
+// https://conwaylife.com/wiki/OCA:Star_Wars
+pub static STAR_WARS: Element = Element {
+    element_type: ElementType::Automaton,
+    color: Some(ORANGE),
+    color_variance: 0.0,
+    name: "Star Wars",
+    rule: Some(AutomatonRule::parse("B2/S345/C4")),
+    ..Element::DEFAULT
+};
+
+// Larger than Life rule with a radius 5 neighbourhood, known for its large gliders ("bugs").
+// https://conwaylife.com/wiki/Bosco%27s_Rule
+pub static BOSCO: Element = Element {
+    element_type: ElementType::Automaton,
+    color: Some(GREEN),
+    color_variance: 0.0,
+    name: "Bosco",
+    rule: Some(AutomatonRule::parse("R5,C0,M1,S34..58,B34..45,NM")),
+    ..Element::DEFAULT
+};
+
+pub static HONEY: Element = Element {
+    element_type: ElementType::Liquid,
+    color: Some(Color::new(0.9, 0.6, 0.1, 1.0)),
+    color_variance: 0.05,
+    name: "Honey",
+    dispersion_rate: 1,
+    viscosity: 0.85,
+    flammability: 0.05,
+    ..Element::DEFAULT
+};
+
+pub static HELIUM: Element = Element {
+    element_type: ElementType::Gas,
+    color: Some(Color::new(1.0, 0.85, 0.9, 1.0)),
+    color_variance: 0.05,
+    name: "Helium",
+    density: 0.15,
+    diffusion_rate: 4,
+    ..Element::DEFAULT
+};
+
+pub static CO2: Element = Element {
+    element_type: ElementType::Gas,
+    color: Some(Color::new(0.55, 0.6, 0.55, 1.0)),
+    color_variance: 0.05,
+    name: "CO2",
+    density: 1.5,
+    diffusion_rate: 1,
+    ..Element::DEFAULT
+};
+
+pub static DUST: Element = Element {
+    element_type: ElementType::MoveableSolid,
+    color: Some(Color::new(0.85, 0.8, 0.7, 1.0)),
+    color_variance: 0.05,
+    name: "Dust",
+    friction: 0.0,
+    flammability: 0.2,
+    density: 0.3,
+    ..Element::DEFAULT
+};
+
+pub static SPONGE: Element = Element {
+    element_type: ElementType::ImmovableSolid,
+    color: Some(Color::new(0.95, 0.9, 0.4, 1.0)),
+    color_variance: 0.05,
+    name: "Sponge",
+    absorbency: 0.05,
+    flammability: 0.1,
+    ..Element::DEFAULT
+};
+
+pub static GRAVEL: Element = Element {
+    element_type: ElementType::MoveableSolid,
+    color: Some(GRAY),
+    color_variance: 0.1,
+    name: "Gravel",
+    hardness: 0.5,
+    friction: 0.4,
+    corrosion_resistance: 0.6,
+    ..Element::DEFAULT
+};
+
+// Fixed point that structures can hang from
+pub static ANCHOR: Element = Element {
+    element_type: ElementType::ImmovableSolid,
+    color: Some(Color::new(0.2, 0.15, 0.1, 1.0)),
+    color_variance: 0.0,
+    name: "Anchor",
+    hardness: 1.0,
+    corrosion_resistance: 1.0,
+    ..Element::DEFAULT
+};
+
+// Light and flammable, floats on water
+pub static WOOD: Element = Element {
+    element_type: ElementType::ImmovableSolid,
+    color: Some(Color::new(0.55, 0.35, 0.15, 1.0)),
+    color_variance: 0.05,
+    name: "Wood",
+    flammability: 0.3,
+    hardness: 0.4,
+    density: 0.6,
+    ..Element::DEFAULT
+};
+
+pub static METAL: Element = Element {
+    element_type: ElementType::ImmovableSolid,
+    color: Some(Color::new(0.55, 0.6, 0.65, 1.0)),
+    color_variance: 0.0,
+    name: "Metal",
+    hardness: 0.9,
+    conductive: true,
+    corrosion_resistance: 0.6,
+    ..Element::DEFAULT
+};
+
+// Charges the conductors touching it for as long as it exists
+pub static BATTERY: Element = Element {
+    element_type: ElementType::ImmovableSolid,
+    color: Some(Color::new(0.1, 0.6, 0.2, 1.0)),
+    color_variance: 0.0,
+    name: "Battery",
+    hardness: 0.9,
+    power_source: true,
+    corrosion_resistance: 0.6,
+    ..Element::DEFAULT
+};
+
+// A single charge that fizzles out right away
+pub static SPARK: Element = Element {
+    element_type: ElementType::ImmovableSolid,
+    color: Some(Color::new(1.0, 1.0, 0.6, 1.0)),
+    color_variance: 0.2,
+    name: "Spark",
+    power_source: true,
+    lifetime: Some(Lifetime {
+        min: 2,
+        max: 2,
+        decays_into: &NOTHING,
+    }),
+    ..Element::DEFAULT
+};
+
+pub static HEATER: Element = Element {
+    element_type: ElementType::ImmovableSolid,
+    color: Some(Color::new(0.6, 0.25, 0.2, 1.0)),
+    color_variance: 0.0,
+    name: "Heater",
+    hardness: 0.9,
+    conductive: true,
+    powered: Some(PoweredEffect::Heat),
+    corrosion_resistance: 0.6,
+    ..Element::DEFAULT
+};
+
+pub static IGNITER: Element = Element {
+    element_type: ElementType::ImmovableSolid,
+    color: Some(Color::new(0.7, 0.5, 0.1, 1.0)),
+    color_variance: 0.0,
+    name: "Igniter",
+    hardness: 0.9,
+    conductive: true,
+    powered: Some(PoweredEffect::Ignite),
+    corrosion_resistance: 0.6,
+    ..Element::DEFAULT
+};
+
+// A faucet that only runs while powered
+pub static PUMP: Element = Element {
+    element_type: ElementType::PixelGenerator,
+    color: Some(Color::new(0.3, 0.5, 0.8, 1.0)),
+    color_variance: 0.0,
+    name: "Pump",
+    emits: Some(&WATER),
+    hardness: 1.0,
+    corrosion_resistance: 1.0,
+    conductive: true,
+    powered: Some(PoweredEffect::Switch),
+    ..Element::DEFAULT
+};
+
+// Falls like a powder until it touches water, then sprouts
+pub static SEED: Element = Element {
+    element_type: ElementType::MoveableSolid,
+    color: Some(Color::new(0.6, 0.45, 0.25, 1.0)),
+    color_variance: 0.05,
+    name: "Seed",
+    flammability: 0.5,
+    reactions: &[Reaction {
+        with: &WATER,
+        chance: 0.05,
+        becomes: &SPROUT,
+        with_becomes: &NOTHING,
+    }],
+    ..Element::DEFAULT
+};
+
+// The growing tip of a plant. Once it has lived out its lifetime it stops growing and
+// leaves a last leaf behind.
+pub static SPROUT: Element = Element {
+    element_type: ElementType::Plant,
+    color: Some(Color::new(0.6, 0.9, 0.3, 1.0)),
+    color_variance: 0.05,
+    name: "Sprout",
+    flammability: 0.4,
+    absorbency: 0.5,
+    growth: Some(Growth {
+        stem: &STEM,
+        leaf: &LEAF,
+        rate: 0.2,
+        leaf_chance: 0.15,
+        clings: false,
+    }),
+    lifetime: Some(Lifetime {
+        min: 600,
+        max: 1800,
+        decays_into: &LEAF,
+    }),
+    ..Element::DEFAULT
+};
+
+pub static STEM: Element = Element {
+    element_type: ElementType::Plant,
+    color: Some(Color::new(0.2, 0.55, 0.15, 1.0)),
+    color_variance: 0.05,
+    name: "Stem",
+    flammability: 0.4,
+    absorbency: 0.2,
+    ..Element::DEFAULT
+};
+
+pub static LEAF: Element = Element {
+    element_type: ElementType::Plant,
+    color: Some(Color::new(0.3, 0.8, 0.2, 1.0)),
+    color_variance: 0.1,
+    name: "Leaf",
+    flammability: 0.6,
+    absorbency: 0.2,
+    ..Element::DEFAULT
+};
+
+// A climbing plant that creeps along whatever surface it is placed against
+pub static VINE: Element = Element {
+    element_type: ElementType::Plant,
+    color: Some(Color::new(0.5, 0.8, 0.35, 1.0)),
+    color_variance: 0.05,
+    name: "Vine",
+    flammability: 0.4,
+    absorbency: 0.5,
+    growth: Some(Growth {
+        stem: &VINE_STEM,
+        leaf: &LEAF,
+        rate: 0.2,
+        leaf_chance: 0.1,
+        clings: true,
+    }),
+    lifetime: Some(Lifetime {
+        min: 900,
+        max: 2400,
+        decays_into: &LEAF,
+    }),
+    ..Element::DEFAULT
+};
+
+pub static VINE_STEM: Element = Element {
+    element_type: ElementType::Plant,
+    color: Some(Color::new(0.25, 0.45, 0.2, 1.0)),
+    color_variance: 0.05,
+    name: "Vine stem",
+    flammability: 0.4,
+    absorbency: 0.2,
+    ..Element::DEFAULT
+};
+
+// Eats through most solids, using itself up and giving off fumes as it goes
+pub static ACID: Element = Element {
+    element_type: ElementType::Liquid,
+    color: Some(Color::new(0.5, 0.95, 0.1, 1.0)),
+    color_variance: 0.1,
+    name: "Acid",
+    corrosiveness: 0.2,
+    corrosion_resistance: 1.0,
+    ..Element::DEFAULT
+};
+
+pub static FUMES: Element = Element {
+    element_type: ElementType::Gas,
+    color: Some(Color::new(0.7, 0.85, 0.5, 1.0)),
+    color_variance: 0.1,
+    name: "Fumes",
+    density: 0.8,
+    diffusion_rate: 2,
+    lifetime: Some(Lifetime {
+        min: 60,
+        max: 180,
+        decays_into: &NOTHING,
+    }),
+    ..Element::DEFAULT
+};
+
+// Flows slowly, sets things alight and slowly cools into stone, or all at once when it
+// touches water
+pub static LAVA: Element = Element {
+    element_type: ElementType::Liquid,
+    color: Some(Color::new(1.0, 0.35, 0.05, 1.0)),
+    color_variance: 0.15,
+    name: "Lava",
+    dispersion_rate: 1,
+    viscosity: 0.6,
+    density: 3.0,
+    hot: true,
+    glow: 4.0,
+    lifetime: Some(Lifetime {
+        min: 900,
+        max: 2400,
+        decays_into: &STONE,
+    }),
+    reactions: &[Reaction {
+        with: &WATER,
+        chance: 0.3,
+        becomes: &STONE,
+        with_becomes: &STEAM,
+    }],
+    corrosion_resistance: 1.0,
+    ..Element::DEFAULT
+};
+
+// Rises until it cools, then condenses back into water
+pub static STEAM: Element = Element {
+    element_type: ElementType::Gas,
+    color: Some(Color::new(0.85, 0.85, 0.9, 1.0)),
+    color_variance: 0.05,
+    name: "Steam",
+    density: 0.4,
+    diffusion_rate: 2,
+    condenses_into: Some(&WATER),
+    ..Element::DEFAULT
 };
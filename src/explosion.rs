@@ -0,0 +1,76 @@
+use ::rand::{thread_rng, Rng};
+
+use crate::element_type::ElementType;
+use crate::elements::{FIRE, NOTHING, SMOKE};
+use crate::grid::{Grid, Vector2};
+
+// Fraction of the cleared area that is left burning, and left smoking
+const FIRE_CHANCE: f32 = 0.3;
+const SMOKE_CHANCE: f32 = 0.3;
+// How far past the blast radius the pressure wave reaches, as a multiple of the radius
+const SHOCKWAVE_REACH: f32 = 2.0;
+// Speed, in cells per tick, given to loose material at the edge of the blast
+const SHOCKWAVE_SPEED: f32 = 6.0;
+
+// Blow up the explosive at the given position. Everything within `radius` is destroyed
+// unless its hardness lets it resist, leaving some fire and smoke behind. Loose material
+// further out is thrown away from the blast as particles. Any other explosives caught
+// in the blast go off too.
+pub fn explode(grid: &mut Grid, center: Vector2, radius: usize) {
+    let mut pending = vec![(center, radius)];
+    while let Some((center, radius)) = pending.pop() {
+        grid.set(center, &NOTHING);
+        blast(grid, center, radius, &mut pending);
+    }
+}
+
+fn blast(grid: &mut Grid, center: Vector2, radius: usize, pending: &mut Vec<(Vector2, usize)>) {
+    let mut rng = thread_rng();
+    let reach = (radius as f32 * SHOCKWAVE_REACH).ceil() as isize;
+
+    for dy in -reach..=reach {
+        for dx in -reach..=reach {
+            let Some(pos) = grid.offset(center, (dx, dy)) else {
+                continue;
+            };
+            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+            if distance > reach as f32 {
+                continue;
+            }
+
+            let element = grid.get(pos);
+            if element.explosion_radius > 0 {
+                // Chain reaction
+                if distance <= radius as f32 && !pending.iter().any(|(p, _)| *p == pos) {
+                    pending.push((pos, element.explosion_radius));
+                }
+                continue;
+            }
+
+            if distance <= radius as f32 {
+                if rng.gen::<f32>() < element.hardness {
+                    continue;
+                }
+                let roll = rng.gen::<f32>();
+                if roll < FIRE_CHANCE {
+                    grid.set(pos, &FIRE);
+                } else if roll < FIRE_CHANCE + SMOKE_CHANCE {
+                    grid.set(pos, &SMOKE);
+                } else {
+                    grid.set(pos, &NOTHING);
+                }
+            } else if matches!(
+                element.element_type,
+                ElementType::MoveableSolid | ElementType::Liquid
+            ) {
+                // The pressure wave weakens the further out it gets
+                let strength = 1.0 - (distance - radius as f32) / (reach as f32 - radius as f32);
+                let speed = SHOCKWAVE_SPEED * strength;
+                grid.eject(
+                    pos,
+                    (dx as f32 / distance * speed, dy as f32 / distance * speed),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::element_type::GRAVITY;
+
+// Which way gravity pulls and how hard. Cells can only fall into their neighbors, so gravity
+// always pulls straight along one of the grid's axes. `down` is the offset of the neighbor
+// gravity pulls towards, and `strength` scales the normal pull, with 0 being zero-g. Even in
+// zero-g, `down` still decides which way is up for things that grow or rise.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Gravity {
+    pub down: (isize, isize),
+    pub strength: f32,
+}
+
+impl Gravity {
+    pub const NORMAL: Gravity = Gravity {
+        down: (0, 1),
+        strength: 1.0,
+    };
+
+    pub fn is_zero_g(&self) -> bool {
+        self.strength <= 0.0
+    }
+
+    // Acceleration of falling things, in cells per tick per tick
+    pub fn acceleration(&self) -> (f32, f32) {
+        let pull = GRAVITY * self.strength;
+        (self.down.0 as f32 * pull, self.down.1 as f32 * pull)
+    }
+
+    // The offset `across` cells sideways and `down` cells along gravity, where sideways is
+    // down turned a quarter turn. Under normal gravity offsets come out unchanged.
+    pub fn relative(&self, (across, down): (isize, isize)) -> (isize, isize) {
+        let (dx, dy) = self.down;
+        (dy * across + dx * down, -dx * across + dy * down)
+    }
+
+    // Split a velocity into its sideways and downward parts
+    pub fn to_relative(&self, (vx, vy): (f32, f32)) -> (f32, f32) {
+        let (dx, dy) = (self.down.0 as f32, self.down.1 as f32);
+        (vx * dy - vy * dx, vx * dx + vy * dy)
+    }
+
+    // Put a velocity split by `to_relative` back together
+    pub fn from_relative(&self, (across, down): (f32, f32)) -> (f32, f32) {
+        let (dx, dy) = (self.down.0 as f32, self.down.1 as f32);
+        (dy * across + dx * down, -dx * across + dy * down)
+    }
+
+    // Turn the direction gravity pulls in a quarter turn clockwise
+    pub fn rotate(&mut self) {
+        let (dx, dy) = self.down;
+        self.down = (-dy, dx);
+    }
+}
@@ -1,8 +1,24 @@
+use std::collections::HashMap;
+
+use ::rand::{thread_rng, Rng};
+
+use crate::automaton::step_automata;
+use crate::boundary::{Boundaries, Boundary, Edge};
+use crate::cell::Cell;
+use crate::electricity::step_electricity;
+use crate::element_type::ElementType;
 use crate::elements::{Element, NOTHING};
+use crate::gravity::Gravity;
+use crate::particles::{step_particles, Particle};
+use crate::pressure::{step_pressure, LevelBodies};
+use crate::rigid_body::{step_bodies, RigidBody};
+use crate::structure::step_structure;
+use crate::wind::WindField;
 
 // constants
-pub const GRID_WIDTH: usize = 800;
-pub const GRID_HEIGHT: usize = 400;
+// Size of the grid in cells unless another size is asked for at startup
+pub const DEFAULT_WIDTH: usize = 800;
+pub const DEFAULT_HEIGHT: usize = 400;
 
 pub type Vector2 = (usize, usize);
 
@@ -10,62 +26,357 @@ pub struct Grid {
     pub width: usize,
     pub height: usize,
     pub cell_size: f32,
-    elements: Vec<Element>,
+    cells: Vec<Cell>,
+    pub particles: Vec<Particle>,
+    // Number of cells of each element consumed by drains, keyed by element name
+    pub drained: HashMap<&'static str, u64>,
+    // Whether unsupported structural solids collapse
+    pub structural: bool,
+    pub bodies: Vec<RigidBody>,
+    next_body_id: u32,
+    pub wind: WindField,
+    pub gravity: Gravity,
+    pub boundaries: Boundaries,
+    pub level_bodies: LevelBodies,
+    // Flipped every update so cells that already moved this update aren't stepped twice
+    clock: bool,
 }
 
 impl Grid {
-    // Create a new grid with the given width and height
-    pub fn new(screen_height: f32) -> Grid {
+    // Create a new grid with the given width and height in cells
+    pub fn new(width: usize, height: usize) -> Grid {
         Grid {
-            width: GRID_WIDTH,
-            height: GRID_HEIGHT,
-            cell_size: screen_height / GRID_HEIGHT as f32,
-            elements: vec![NOTHING; GRID_WIDTH * GRID_HEIGHT],
+            width,
+            height,
+            cell_size: 1.0,
+            cells: vec![Cell::default(); width * height],
+            particles: Vec::new(),
+            drained: HashMap::new(),
+            structural: false,
+            bodies: Vec::new(),
+            next_body_id: 0,
+            wind: WindField::new(width, height),
+            gravity: Gravity::NORMAL,
+            boundaries: Boundaries::WALLS,
+            level_bodies: LevelBodies::new(width, height),
+            clock: false,
         }
     }
     // Get the element at the given position
-    pub fn get(&self, pos: Vector2) -> Element {
+    pub fn get(&self, pos: Vector2) -> &'static Element {
         // Return air if the position is out of bounds
         // Otherwise, return the element at the given position
-        if pos.0 < self.width && pos.1 < self.height {
-            return self.elements[pos.1 * self.width + pos.0];
+        match self.get_cell(pos) {
+            Some(cell) => cell.element,
+            None => &NOTHING,
+        }
+    }
+    // Set the element at the given position, replacing whatever cell was there
+    pub fn set(&mut self, pos: Vector2, value: &'static Element) {
+        let clock = self.clock;
+        if let Some(cell) = self.get_cell_mut(pos) {
+            *cell = Cell::new(value);
+            cell.clock = clock;
+        }
+        self.disturb(pos);
+        if *value == NOTHING {
+            self.wake_neighbors(pos);
         }
-        NOTHING
     }
-    // Set the element at the given position
-    pub fn set(&mut self, pos: Vector2, value: Element) {
-        if pos.0 < self.width && pos.1 < self.height {
-            self.elements[pos.1 * self.width + pos.0] = value;
+
+    // Get the cell at the given position, if it is within bounds
+    pub fn get_cell(&self, pos: Vector2) -> Option<&Cell> {
+        if self.is_within_bounds(pos) {
+            return Some(&self.cells[pos.1 * self.width + pos.0]);
         }
+        None
+    }
+
+    pub fn get_cell_mut(&mut self, pos: Vector2) -> Option<&mut Cell> {
+        if self.is_within_bounds(pos) {
+            return Some(&mut self.cells[pos.1 * self.width + pos.0]);
+        }
+        None
     }
 
     // Move the element at the given position to the new position
     pub fn move_element(&mut self, pos: Vector2, new_pos: Vector2) {
-        let element = self.get(pos);
-        self.set(pos, NOTHING);
-        self.set(new_pos, element);
+        if !self.is_within_bounds(pos) || !self.is_within_bounds(new_pos) {
+            return;
+        }
+        let index = pos.1 * self.width + pos.0;
+        let new_index = new_pos.1 * self.width + new_pos.0;
+        self.cells[new_index] = self.cells[index];
+        self.cells[new_index].clock = self.clock;
+        self.cells[index] = Cell::default();
+        self.wake_neighbors(pos);
+        self.disturb(pos);
+        self.disturb(new_pos);
     }
 
     // Swap the elements at the given positions
     pub fn swap_elements(&mut self, pos: Vector2, new_pos: Vector2) {
-        let element1 = self.get(pos);
-        let element2 = self.get(new_pos);
-        self.set(pos, element2);
-        self.set(new_pos, element1);
+        if !self.is_within_bounds(pos) || !self.is_within_bounds(new_pos) {
+            return;
+        }
+        let index = pos.1 * self.width + pos.0;
+        let new_index = new_pos.1 * self.width + new_pos.0;
+        self.cells.swap(index, new_index);
+        self.cells[index].clock = self.clock;
+        self.cells[new_index].clock = self.clock;
+        self.disturb(pos);
+        self.disturb(new_pos);
+    }
+
+    // Let any liquid body in or next to the given position be checked for pressure again
+    fn disturb(&mut self, pos: Vector2) {
+        for offset in [(0, 0), (0, -1), (-1, 0), (1, 0), (0, 1)] {
+            if let Some((x, y)) = self.offset(pos, offset) {
+                self.level_bodies.disturb(y * self.width + x);
+            }
+        }
+    }
+
+    // Every cell in the grid, in row-major order
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [Cell] {
+        &mut self.cells
+    }
+
+    // Count a cell of the given element as consumed by a drain
+    pub fn record_drained(&mut self, element: &Element) {
+        *self.drained.entry(element.name).or_insert(0) += 1;
+    }
+
+    pub fn set_free_falling(&mut self, pos: Vector2, free_falling: bool) {
+        if let Some(cell) = self.get_cell_mut(pos) {
+            cell.free_falling = free_falling;
+        }
+    }
+
+    // Something moved out of the given position, so resting powders beside and above it
+    // may start moving again. Each one resists with a chance equal to its friction.
+    fn wake_neighbors(&mut self, pos: Vector2) {
+        let mut rng = thread_rng();
+        for offset in [(-1, 0), (1, 0), (-1, -1), (0, -1), (1, -1)] {
+            let Some(neighbor) = self.offset(pos, self.gravity.relative(offset)) else {
+                continue;
+            };
+            if let Some(cell) = self.get_cell_mut(neighbor) {
+                let is_powder = cell.element.element_type == ElementType::MoveableSolid;
+                if is_powder && !cell.free_falling && rng.gen::<f32>() >= cell.element.friction {
+                    cell.free_falling = true;
+                }
+            }
+        }
+    }
+
+    // Get the velocity of the cell at the given position
+    pub fn get_velocity(&self, pos: Vector2) -> (f32, f32) {
+        self.get_cell(pos).map_or((0.0, 0.0), |cell| cell.velocity)
+    }
+
+    // Set the velocity of the cell at the given position
+    pub fn set_velocity(&mut self, pos: Vector2, velocity: (f32, f32)) {
+        if let Some(cell) = self.get_cell_mut(pos) {
+            cell.velocity = velocity;
+        }
     }
 
     // Update the grid
     pub fn update(&mut self) {
-        for y in (0..self.height).rev() {
-            for x in 0..self.width {
-                let element = self.get((x, y));
+        step_automata(self);
+        step_electricity(self);
+        let zero_g = self.gravity.is_zero_g();
+        if self.structural && !zero_g {
+            step_structure(self);
+        }
+        self.clock = !self.clock;
+        // Scan from the side gravity pulls towards, so cells that fall move out of the way
+        // of the cells above them before those are stepped. Each line across gravity is
+        // scanned in alternating directions so nothing drifts to one side.
+        let (dx, dy) = self.gravity.down;
+        let sideways = dx != 0;
+        let (lines, line_length) = if sideways {
+            (self.width, self.height)
+        } else {
+            (self.height, self.width)
+        };
+        for l in 0..lines {
+            let line = if dx + dy < 0 { l } else { lines - 1 - l };
+            for i in 0..line_length {
+                let along = if self.clock { i } else { line_length - 1 - i };
+                let (x, y) = if sideways {
+                    (line, along)
+                } else {
+                    (along, line)
+                };
+                let cell = &mut self.cells[y * self.width + x];
+                // Rigid body cells are moved by their body instead
+                if cell.clock == self.clock || cell.body.is_some() {
+                    continue;
+                }
+                cell.clock = self.clock;
+                let element = cell.element;
                 element.step(self, x, y);
             }
         }
+        self.drop_into_void();
+        if !zero_g {
+            step_pressure(self);
+        }
+        step_bodies(self);
+        step_particles(self);
+        self.age_cells();
+    }
+
+    // Where the given position ends up once it has been taken through the edges of the world.
+    // Positions past a wrapping edge come back in on the opposite side, while positions past
+    // a wall or the void give the boundary in the way.
+    pub fn resolve(&self, (x, y): (isize, isize)) -> Result<Vector2, Boundary> {
+        let x = self.resolve_axis(x, self.width, Edge::Left, Edge::Right)?;
+        let y = self.resolve_axis(y, self.height, Edge::Top, Edge::Bottom)?;
+        Ok((x, y))
+    }
+
+    fn resolve_axis(
+        &self,
+        coordinate: isize,
+        size: usize,
+        low: Edge,
+        high: Edge,
+    ) -> Result<usize, Boundary> {
+        let size = size as isize;
+        let edge = if coordinate < 0 {
+            low
+        } else if coordinate >= size {
+            high
+        } else {
+            return Ok(coordinate as usize);
+        };
+        match self.boundaries.get(edge) {
+            Boundary::Wrap => Ok(coordinate.rem_euclid(size) as usize),
+            boundary => Err(boundary),
+        }
+    }
+
+    // The position `offset` away from `pos`, if the edges of the world let anything get there
+    pub fn offset(&self, pos: Vector2, offset: (isize, isize)) -> Option<Vector2> {
+        self.resolve((pos.0 as isize + offset.0, pos.1 as isize + offset.1))
+            .ok()
+    }
+
+    // The neighbors gravity pulls towards and away from
+    pub fn below(&self, pos: Vector2) -> Option<Vector2> {
+        self.offset(pos, self.gravity.relative((0, 1)))
+    }
+
+    pub fn above(&self, pos: Vector2) -> Option<Vector2> {
+        self.offset(pos, self.gravity.relative((0, -1)))
+    }
+
+    // How far the given position is below the top of the world, as gravity sees it
+    pub fn depth(&self, pos: Vector2) -> usize {
+        match self.gravity.down {
+            (0, -1) => self.height - 1 - pos.1,
+            (1, 0) => pos.0,
+            (-1, 0) => self.width - 1 - pos.0,
+            _ => pos.1,
+        }
     }
 
-    pub fn update_cell_size(&mut self, screen_height: f32) {
-        self.cell_size = screen_height / GRID_HEIGHT as f32;
+    // Add a rigid body built by the given function, which is passed the id to use. Returns
+    // whether it was added, which it isn't if it would overlap anything solid.
+    pub fn add_body<F>(&mut self, build: F) -> bool
+    where
+        F: FnOnce(u32) -> RigidBody,
+    {
+        let body = build(self.next_body_id);
+        if !body.fits(self) {
+            return false;
+        }
+        self.next_body_id += 1;
+        self.bodies.push(body);
+        true
+    }
+
+    // Throw the cell at the given position out of the grid as a free particle
+    pub fn eject(&mut self, pos: Vector2, velocity: (f32, f32)) {
+        let element = self.get(pos);
+        if *element == NOTHING {
+            return;
+        }
+        self.set(pos, &NOTHING);
+        self.particles.push(Particle {
+            element,
+            position: (pos.0 as f32 + 0.5, pos.1 as f32 + 0.5),
+            velocity,
+        });
+    }
+
+    // Age every cell by one tick. This runs as a separate pass so that cells which
+    // moved during the update are still only aged once.
+    fn age_cells(&mut self) {
+        let mut decayed = Vec::new();
+        for (index, cell) in self.cells.iter_mut().enumerate() {
+            if cell.tick() {
+                decayed.push((index % self.width, index / self.width));
+            }
+        }
+        for pos in decayed {
+            self.disturb(pos);
+        }
+    }
+
+    // Make cells as big as they can be while the whole grid still fits on the screen
+    pub fn update_cell_size(&mut self, screen_width: f32, screen_height: f32) {
+        self.cell_size = (screen_width / self.width as f32).min(screen_height / self.height as f32);
+    }
+
+    // Change the size of the grid, keeping everything in it. The contents stay against the
+    // side of the world gravity pulls towards, so what was resting on the ground still is,
+    // and whatever no longer fits is cropped.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let (down_x, down_y) = self.gravity.down;
+        let shift = (
+            if down_x > 0 {
+                width as isize - self.width as isize
+            } else {
+                0
+            },
+            if down_y < 0 {
+                0
+            } else {
+                height as isize - self.height as isize
+            },
+        );
+
+        let mut cells = vec![Cell::default(); width * height];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let new_x = x as isize + shift.0;
+                let new_y = y as isize + shift.1;
+                if new_x < 0 || new_y < 0 || new_x >= width as isize || new_y >= height as isize {
+                    continue;
+                }
+                cells[new_y as usize * width + new_x as usize] = self.cells[y * self.width + x];
+            }
+        }
+        self.cells = cells;
+        self.width = width;
+        self.height = height;
+        self.wind.resize(width, height, shift);
+        self.level_bodies = LevelBodies::new(width, height);
+        for body in self.bodies.iter_mut() {
+            body.shift(shift, width, height);
+        }
+        for particle in self.particles.iter_mut() {
+            particle.position.0 += shift.0 as f32;
+            particle.position.1 += shift.1 as f32;
+        }
     }
 
     // Apply the function to each element in between two positions
@@ -91,11 +402,162 @@ impl Grid {
             y += y_increment;
         }
     }
+
+    // Walk from start in a straight line towards `offset` away and return the furthest
+    // position that can be reached without passing through a cell that `is_free` rejects or
+    // past an edge of the world. Returns start if the very first step is blocked.
+    pub fn last_free_along<F>(&self, start: Vector2, offset: (isize, isize), is_free: F) -> Vector2
+    where
+        F: Fn(&Element) -> bool,
+    {
+        let steps = offset.0.abs().max(offset.1.abs());
+        let mut last = start;
+        for step in 1..=steps {
+            let fraction = step as f32 / steps as f32;
+            let delta = (
+                (offset.0 as f32 * fraction).round() as isize,
+                (offset.1 as f32 * fraction).round() as isize,
+            );
+            match self.offset(start, delta) {
+                Some(pos) if is_free(self.get(pos)) => last = pos,
+                _ => break,
+            }
+        }
+        last
+    }
+
+    // Cells that reach a void edge fall out of the world. Powders and liquids fall through the
+    // edge gravity pulls them towards, liquids also flow out through the sides, and gases and
+    // fire drift out through any void edge at all.
+    fn drop_into_void(&mut self) {
+        let falls = !self.gravity.is_zero_g();
+        let down = self.gravity.relative((0, 1));
+        let up = self.gravity.relative((0, -1));
+        for edge in Edge::ALL {
+            if self.boundaries.get(edge) != Boundary::Void {
+                continue;
+            }
+            let outward = edge.outward();
+            let is_below = falls && outward == down;
+            let is_side = falls && outward != down && outward != up;
+            for pos in self.edge_positions(edge) {
+                let cell = &self.cells[pos.1 * self.width + pos.0];
+                if cell.body.is_some() {
+                    continue;
+                }
+                let drops = match cell.element.element_type {
+                    ElementType::MoveableSolid => is_below,
+                    ElementType::Liquid => is_below || is_side,
+                    ElementType::Gas | ElementType::Fire => true,
+                    _ => false,
+                };
+                if drops {
+                    self.set(pos, &NOTHING);
+                }
+            }
+        }
+    }
+
+    // Every position along the given edge of the grid
+    fn edge_positions(&self, edge: Edge) -> Vec<Vector2> {
+        let (width, height) = (self.width, self.height);
+        match edge {
+            Edge::Left => (0..height).map(|y| (0, y)).collect(),
+            Edge::Right => (0..height).map(|y| (width - 1, y)).collect(),
+            Edge::Top => (0..width).map(|x| (x, 0)).collect(),
+            Edge::Bottom => (0..width).map(|x| (x, height - 1)).collect(),
+        }
+    }
+
     pub fn is_within_bounds(&self, pos: Vector2) -> bool {
         pos.0 < self.width && pos.1 < self.height
     }
 
     pub fn reset(&mut self) {
-        self.elements = vec![NOTHING; self.width * self.height];
+        self.cells = vec![Cell::default(); self.width * self.height];
+        self.particles.clear();
+        self.bodies.clear();
+        self.wind.clear();
+        self.drained.clear();
+        self.level_bodies = LevelBodies::new(self.width, self.height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boundary::{Boundary, Edge};
+    use crate::elements::{SAND, STONE, WATER, WOOD};
+
+    #[test]
+    fn resize_keeps_contents_on_the_ground() {
+        let mut grid = Grid::new(100, 100);
+        grid.set((10, 99), &STONE);
+        grid.set((90, 0), &SAND);
+
+        grid.resize(50, 150);
+        assert_eq!((grid.width, grid.height), (50, 150));
+        assert!(*grid.get((10, 149)) == STONE);
+        assert!(*grid.get((10, 99)) == NOTHING);
+        // The sand was past the new right edge, so it is cropped
+        assert!(grid.cells().iter().all(|cell| *cell.element != SAND));
+
+        grid.resize(50, 60);
+        assert!(*grid.get((10, 59)) == STONE);
+        assert_eq!(grid.wind.at((49, 59)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn bodies_are_not_added_over_solids() {
+        let mut grid = Grid::new(40, 40);
+        grid.set((20, 20), &STONE);
+        assert!(!grid.add_body(|id| RigidBody::rectangle(id, (20, 20), 4, 4, &WOOD)));
+        assert!(grid.bodies.is_empty());
+        assert!(*grid.get((20, 20)) == STONE);
+
+        assert!(grid.add_body(|id| RigidBody::rectangle(id, (20, 10), 4, 4, &WOOD)));
+        assert_eq!(grid.bodies.len(), 1);
+    }
+
+    #[test]
+    fn sand_falls_through_a_void_floor() {
+        let mut grid = Grid::new(20, 20);
+        grid.boundaries.set(Edge::Bottom, Boundary::Void);
+        for x in 5..15 {
+            grid.set((x, 5), &SAND);
+        }
+
+        for _ in 0..50 {
+            grid.update();
+        }
+        assert!(grid.cells().iter().all(|cell| *cell.element != SAND));
+    }
+
+    #[test]
+    fn water_drains_out_of_a_void_side() {
+        let mut grid = Grid::new(20, 20);
+        grid.boundaries.set(Edge::Left, Boundary::Void);
+        for x in 0..10 {
+            for y in 17..20 {
+                grid.set((x, y), &WATER);
+            }
+        }
+
+        for _ in 0..300 {
+            grid.update();
+        }
+        assert!(grid.cells().iter().all(|cell| *cell.element != WATER));
+    }
+
+    #[test]
+    fn offset_wraps_around_the_world() {
+        let mut grid = Grid::new(20, 10);
+        assert!(grid.offset((0, 5), (-1, 0)).is_none());
+
+        grid.boundaries.set(Edge::Left, Boundary::Wrap);
+        assert_eq!(grid.offset((0, 5), (-1, 0)), Some((19, 5)));
+        assert_eq!(grid.offset((19, 5), (3, 0)), Some((2, 5)));
+        // Top and bottom are still walls
+        assert!(grid.offset((0, 9), (0, 1)).is_none());
     }
 }
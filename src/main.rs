@@ -1,14 +1,91 @@
 use macroquad::prelude::*;
 
+pub mod automaton;
+pub mod boundary;
+pub mod cell;
+pub mod electricity;
 pub mod element_type;
 pub mod elements;
+pub mod explosion;
+pub mod gravity;
 pub mod grid;
+pub mod particles;
+pub mod pressure;
+pub mod rigid_body;
+pub mod structure;
+pub mod wind;
 use ::rand::{thread_rng, Rng};
-use elements::{Element, AIR, CLAY, DRAIN, FAUCET, FIRE, MAZE, NOTHING, SAND, STONE, WATER};
-use grid::Grid;
+use boundary::Edge;
+use elements::{
+    Element, ACID, AIR, ANCHOR, ASH, BATTERY, BOSCO, BRIANS_BRAIN, CLAY, CLONE, CO2, DAY_AND_NIGHT,
+    DRAIN, DUST, EMBER, FAUCET, FIRE, GAS_DRAIN, GAS_VENT, GRAVEL, GUNPOWDER, HEATER, HELIUM,
+    HIGHLIFE, HONEY, IGNITER, LAVA, LIFE, LIQUID_DRAIN, MAZE, METAL, NOTHING, PUMP, SAND,
+    SAND_SPOUT, SEED, SMOKE, SPARK, SPONGE, STAR_WARS, STEAM, STONE, TNT, VINE, WATER, WATER_DRAIN,
+    WOOD,
+};
+use grid::{Grid, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+use rigid_body::RigidBody;
+use wind::{MAX_WIND, WIND_REGION_SIZE};
 
 // Constants
 const BACKGROUND_COLOR: Color = BLACK;
+// Opacity of the halo drawn around each glowing cell
+const GLOW_ALPHA: f32 = 0.06;
+// Wind painted by the wind brushes, and how much each press changes the global wind by
+const WIND_BRUSH_STRENGTH: f32 = 1.0;
+const GLOBAL_WIND_STEP: f32 = 0.25;
+// How much each press changes the strength of gravity by, and the strongest it can get
+const GRAVITY_STEP: f32 = 0.25;
+const MAX_GRAVITY: f32 = 3.0;
+// Factor each press grows or shrinks the grid by, and the smallest and largest the grid
+// can get
+const RESIZE_STEP: f32 = 1.25;
+const MIN_GRID_SIZE: usize = 50;
+const MAX_GRID_SIZE: usize = 1600;
+// Controls handled directly in the main loop rather than by the control manager
+const EXTRA_CONTROLS: [&str; 7] = [
+    "esc: quit, r: reset, i: drain counter, f1: structural",
+    "f2: crate, f3: boulder, f4: plank",
+    "f5: element/wind/vortex brush, f6/f7: global wind",
+    "f8: calm, f9: wind arrows",
+    "f10: turn gravity, f11/f12: weaker/stronger gravity",
+    "arrows: switch that edge between wall/void/wrap",
+    "page up/down: bigger/smaller grid",
+];
+
+// What the mouse paints with
+#[derive(Clone, Copy, PartialEq)]
+enum BrushMode {
+    Element,
+    // Wind blowing the way the mouse is dragged
+    Wind,
+    // Wind swirling clockwise around the cursor
+    Vortex,
+}
+
+// The grid size asked for with `--size WIDTHxHEIGHT`, kept within the sizes the grid can
+// be, or the default size
+fn grid_size_from_args() -> (usize, usize) {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg != "--size" {
+            continue;
+        }
+        let size = args.next().unwrap_or_default();
+        let parsed = size
+            .split_once('x')
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+        match parsed {
+            Some((width, height)) => return (clamp_grid_size(width), clamp_grid_size(height)),
+            None => eprintln!("ignoring --size {size}: expected WIDTHxHEIGHT"),
+        }
+    }
+    (DEFAULT_WIDTH, DEFAULT_HEIGHT)
+}
+
+fn clamp_grid_size(size: usize) -> usize {
+    size.clamp(MIN_GRID_SIZE, MAX_GRID_SIZE)
+}
 
 fn window_conf() -> Conf {
     Conf {
@@ -20,8 +97,9 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf())]
 async fn main() {
-    let mut grid = Grid::new(screen_height());
-    let mut selected_element = SAND;
+    let (width, height) = grid_size_from_args();
+    let mut grid = Grid::new(width, height);
+    let mut selected_element: &'static Element = &SAND;
     let mut brush_size = 1;
 
     // The Control Manager stores all of our controls in a neat and tidy way.
@@ -30,49 +108,224 @@ async fn main() {
     // Add all our buttons to the control manager
     control_manager.add_control(
         KeyCode::Z,
-        Box::new(|elem| *elem = WATER),
+        Box::new(|elem| *elem = &WATER),
         String::from("Z: water"),
     );
+    control_manager.add_control(
+        KeyCode::Y,
+        Box::new(|elem| *elem = &HONEY),
+        String::from("Y: honey"),
+    );
     control_manager.add_control(
         KeyCode::X,
-        Box::new(|elem| *elem = SAND),
+        Box::new(|elem| *elem = &SAND),
         String::from("X: sand"),
     );
     control_manager.add_control(
         KeyCode::C,
-        Box::new(|elem| *elem = STONE),
+        Box::new(|elem| *elem = &STONE),
         String::from("C: stone"),
     );
     control_manager.add_control(
         KeyCode::V,
-        Box::new(|elem| *elem = AIR),
+        Box::new(|elem| *elem = &AIR),
         String::from("V: air"),
     );
+    control_manager.add_control(
+        KeyCode::Q,
+        Box::new(|elem| *elem = &HELIUM),
+        String::from("Q: helium"),
+    );
+    control_manager.add_control(
+        KeyCode::D,
+        Box::new(|elem| *elem = &CO2),
+        String::from("D: co2"),
+    );
     control_manager.add_control(
         KeyCode::B,
-        Box::new(|elem| *elem = FAUCET),
+        Box::new(|elem| *elem = &FAUCET),
         String::from("B: faucet"),
     );
+    control_manager.add_control(
+        KeyCode::U,
+        Box::new(|elem| *elem = &SAND_SPOUT),
+        String::from("U: sand spout"),
+    );
+    control_manager.add_control(
+        KeyCode::H,
+        Box::new(|elem| *elem = &GAS_VENT),
+        String::from("H: gas vent"),
+    );
+    control_manager.add_control(
+        KeyCode::K,
+        Box::new(|elem| *elem = &CLONE),
+        String::from("K: clone"),
+    );
     control_manager.add_control(
         KeyCode::L,
-        Box::new(|elem| *elem = CLAY),
+        Box::new(|elem| *elem = &CLAY),
         String::from("L: clay"),
     );
+    control_manager.add_control(
+        KeyCode::P,
+        Box::new(|elem| *elem = &DUST),
+        String::from("P: dust"),
+    );
+    control_manager.add_control(
+        KeyCode::Key7,
+        Box::new(|elem| *elem = &SPONGE),
+        String::from("7: sponge"),
+    );
+    control_manager.add_control(
+        KeyCode::Key8,
+        Box::new(|elem| *elem = &GRAVEL),
+        String::from("8: gravel"),
+    );
+    control_manager.add_control(
+        KeyCode::Key9,
+        Box::new(|elem| *elem = &ANCHOR),
+        String::from("9: anchor"),
+    );
     control_manager.add_control(
         KeyCode::M,
-        Box::new(|elem| *elem = MAZE),
+        Box::new(|elem| *elem = &MAZE),
         String::from("M: maze"),
     );
+    control_manager.add_control(
+        KeyCode::Key1,
+        Box::new(|elem| *elem = &LIFE),
+        String::from("1: life"),
+    );
+    control_manager.add_control(
+        KeyCode::Key2,
+        Box::new(|elem| *elem = &HIGHLIFE),
+        String::from("2: highlife"),
+    );
+    control_manager.add_control(
+        KeyCode::Key3,
+        Box::new(|elem| *elem = &DAY_AND_NIGHT),
+        String::from("3: day & night"),
+    );
+    control_manager.add_control(
+        KeyCode::Key4,
+        Box::new(|elem| *elem = &BRIANS_BRAIN),
+        String::from("4: brian's brain"),
+    );
+    control_manager.add_control(
+        KeyCode::Key5,
+        Box::new(|elem| *elem = &STAR_WARS),
+        String::from("5: star wars"),
+    );
+    control_manager.add_control(
+        KeyCode::Key6,
+        Box::new(|elem| *elem = &BOSCO),
+        String::from("6: bosco"),
+    );
     control_manager.add_control(
         KeyCode::F,
-        Box::new(|elem| *elem = FIRE),
+        Box::new(|elem| *elem = &FIRE),
         String::from("F: fire"),
     );
     control_manager.add_control(
         KeyCode::N,
-        Box::new(|elem| *elem = DRAIN),
+        Box::new(|elem| *elem = &DRAIN),
         String::from("N: drain"),
     );
+    control_manager.add_control(
+        KeyCode::J,
+        Box::new(|elem| *elem = &LIQUID_DRAIN),
+        String::from("J: liquid drain"),
+    );
+    control_manager.add_control(
+        KeyCode::O,
+        Box::new(|elem| *elem = &GAS_DRAIN),
+        String::from("O: gas drain"),
+    );
+    control_manager.add_control(
+        KeyCode::W,
+        Box::new(|elem| *elem = &WATER_DRAIN),
+        String::from("W: water drain"),
+    );
+    control_manager.add_control(
+        KeyCode::S,
+        Box::new(|elem| *elem = &SMOKE),
+        String::from("S: smoke"),
+    );
+    control_manager.add_control(
+        KeyCode::E,
+        Box::new(|elem| *elem = &EMBER),
+        String::from("E: ember"),
+    );
+    control_manager.add_control(
+        KeyCode::A,
+        Box::new(|elem| *elem = &ASH),
+        String::from("A: ash"),
+    );
+    control_manager.add_control(
+        KeyCode::G,
+        Box::new(|elem| *elem = &GUNPOWDER),
+        String::from("G: gunpowder"),
+    );
+    control_manager.add_control(
+        KeyCode::T,
+        Box::new(|elem| *elem = &TNT),
+        String::from("T: tnt"),
+    );
+    control_manager.add_control(
+        KeyCode::Key0,
+        Box::new(|elem| *elem = &METAL),
+        String::from("0: metal"),
+    );
+    control_manager.add_control(
+        KeyCode::Minus,
+        Box::new(|elem| *elem = &BATTERY),
+        String::from("-: battery"),
+    );
+    control_manager.add_control(
+        KeyCode::Equal,
+        Box::new(|elem| *elem = &SPARK),
+        String::from("=: spark"),
+    );
+    control_manager.add_control(
+        KeyCode::Semicolon,
+        Box::new(|elem| *elem = &HEATER),
+        String::from(";: heater"),
+    );
+    control_manager.add_control(
+        KeyCode::Apostrophe,
+        Box::new(|elem| *elem = &IGNITER),
+        String::from("': igniter"),
+    );
+    control_manager.add_control(
+        KeyCode::Comma,
+        Box::new(|elem| *elem = &PUMP),
+        String::from(",: pump"),
+    );
+    control_manager.add_control(
+        KeyCode::Period,
+        Box::new(|elem| *elem = &SEED),
+        String::from(".: seed"),
+    );
+    control_manager.add_control(
+        KeyCode::Slash,
+        Box::new(|elem| *elem = &VINE),
+        String::from("/: vine"),
+    );
+    control_manager.add_control(
+        KeyCode::Backslash,
+        Box::new(|elem| *elem = &ACID),
+        String::from("\\: acid"),
+    );
+    control_manager.add_control(
+        KeyCode::GraveAccent,
+        Box::new(|elem| *elem = &LAVA),
+        String::from("`: lava"),
+    );
+    control_manager.add_control(
+        KeyCode::Tab,
+        Box::new(|elem| *elem = &STEAM),
+        String::from("tab: steam"),
+    );
 
     // Define brush size controls
     control_manager.add_brush_control(
@@ -87,48 +340,95 @@ async fn main() {
     );
 
     let mut rng = thread_rng();
+    let mut show_drained = false;
+    let mut show_wind = false;
+    let mut brush_mode = BrushMode::Element;
+    let mut last_mouse_position = mouse_position();
 
     // main game loop
     loop {
         grid.update();
-        if grid.width != screen_width().round() as usize
-            || screen_height().round() as usize != grid.height
-        {
-            //change in window
-            grid.update_cell_size(screen_height());
-        }
+        handle_resize_input(&mut grid);
+        grid.update_cell_size(screen_width(), screen_height());
 
         //inputs
         if is_key_pressed(KeyCode::R) {
             //put here so it has access to grid. Temp?
             grid.reset();
         }
+        if is_key_pressed(KeyCode::I) {
+            show_drained = !show_drained;
+        }
+        if is_key_pressed(KeyCode::F1) {
+            grid.structural = !grid.structural;
+        }
+        if is_key_pressed(KeyCode::F5) {
+            brush_mode = match brush_mode {
+                BrushMode::Element => BrushMode::Wind,
+                BrushMode::Wind => BrushMode::Vortex,
+                BrushMode::Vortex => BrushMode::Element,
+            };
+        }
+        if is_key_pressed(KeyCode::F6) {
+            grid.wind.global.0 -= GLOBAL_WIND_STEP;
+        }
+        if is_key_pressed(KeyCode::F7) {
+            grid.wind.global.0 += GLOBAL_WIND_STEP;
+        }
+        if is_key_pressed(KeyCode::F8) {
+            grid.wind.clear();
+        }
+        if is_key_pressed(KeyCode::F9) {
+            show_wind = !show_wind;
+        }
+        handle_gravity_input(&mut grid);
+        handle_boundary_input(&mut grid);
+        handle_body_input(&mut grid);
         clear_background(BACKGROUND_COLOR);
         if !control_manager.handle_input(&mut selected_element, &mut brush_size) {
             // When handle_input returns false, it means we've pressed the escape key and want to quit.
             break;
         }
 
-        draw_controls(&control_manager, &selected_element, brush_size);
+        draw_controls(&control_manager, selected_element, brush_mode, brush_size);
 
-        handle_mouse_input(&mut grid, &selected_element, &brush_size);
+        if brush_mode == BrushMode::Element {
+            handle_mouse_input(&mut grid, selected_element, &brush_size);
+        } else {
+            handle_wind_input(&mut grid, brush_mode, brush_size, last_mouse_position);
+        }
+        last_mouse_position = mouse_position();
 
         render_grid(&grid, &mut rng);
 
+        render_glow(&grid);
+
+        render_particles(&grid);
+
+        if show_wind || brush_mode != BrushMode::Element {
+            render_wind(&grid);
+        }
+
         draw_brush_box(&grid, brush_size);
 
+        if show_drained {
+            draw_drained(&grid);
+        }
+
         next_frame().await
     }
 }
 
 fn render_grid(grid: &Grid, rng: &mut impl Rng) {
     //render grid
-    for y in 0..screen_height() as usize - 20 {
-        for x in 0..screen_width() as usize - 20 {
-            let cell = grid.get((x, y));
-            let color = match cell.get_color() {
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            let Some(cell) = grid.get_cell((x, y)) else {
+                continue;
+            };
+            let color = match cell.color() {
                 Some(color) => {
-                    let variance = cell.color_variance;
+                    let variance = cell.element.color_variance;
                     if variance == 0.0 {
                         color
                     } else {
@@ -154,11 +454,99 @@ fn render_grid(grid: &Grid, rng: &mut impl Rng) {
     }
 }
 
-fn draw_controls(control_manager: &ControlManager, selected_element: &Element, brush_size: usize) {
+// Draw a soft halo of light around glowing cells. Only cells facing open space are lit, so
+// the inside of a big glowing pool doesn't pile up light.
+fn render_glow(grid: &Grid) {
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            let Some(cell) = grid.get_cell((x, y)) else {
+                continue;
+            };
+            let glow = cell.element.glow;
+            if glow <= 0.0 {
+                continue;
+            }
+            let exposed = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .into_iter()
+                .filter_map(|offset| grid.offset((x, y), offset))
+                .any(|pos| *grid.get(pos) == NOTHING);
+            if !exposed {
+                continue;
+            }
+            let Some(color) = cell.element.get_color() else {
+                continue;
+            };
+            draw_circle(
+                (x as f32 + 0.5) * grid.cell_size,
+                (y as f32 + 0.5) * grid.cell_size,
+                glow * grid.cell_size,
+                Color::new(color.r, color.g, color.b, GLOW_ALPHA),
+            );
+        }
+    }
+}
+
+// Draw an arrow for the wind blowing in each region of the wind field
+fn render_wind(grid: &Grid) {
+    let color = Color::new(0.8, 0.9, 1.0, 0.5);
+    for ((x, y), (wx, wy)) in grid.wind.regions() {
+        let strength = wx.hypot(wy);
+        if strength < 0.01 {
+            continue;
+        }
+        // Full strength wind spans a whole region
+        let length = strength.min(MAX_WIND) / MAX_WIND * WIND_REGION_SIZE as f32 * grid.cell_size;
+        let direction = (wx / strength, wy / strength);
+        let start = (
+            x * grid.cell_size - direction.0 * length / 2.0,
+            y * grid.cell_size - direction.1 * length / 2.0,
+        );
+        let end = (
+            start.0 + direction.0 * length,
+            start.1 + direction.1 * length,
+        );
+        draw_line(start.0, start.1, end.0, end.1, 1.0, color);
+        let head = length.min(4.0 * grid.cell_size) / 3.0;
+        for side in [-1.0, 1.0] {
+            // Two short strokes angled back from the tip
+            let back = (
+                -direction.0 * head - side * direction.1 * head * 0.6,
+                -direction.1 * head + side * direction.0 * head * 0.6,
+            );
+            draw_line(end.0, end.1, end.0 + back.0, end.1 + back.1, 1.0, color);
+        }
+    }
+}
+
+fn render_particles(grid: &Grid) {
+    for particle in &grid.particles {
+        if let Some(color) = particle.element.get_color() {
+            draw_rectangle(
+                particle.position.0.floor() * grid.cell_size,
+                particle.position.1.floor() * grid.cell_size,
+                grid.cell_size,
+                grid.cell_size,
+                color,
+            );
+        }
+    }
+}
+
+fn draw_controls(
+    control_manager: &ControlManager,
+    selected_element: &Element,
+    brush_mode: BrushMode,
+    brush_size: usize,
+) {
     let top_of_text = 20.0;
     let framerate: String = String::from("fps: ") + &get_fps().to_string();
     draw_text(&framerate, 10.0, top_of_text, 20.0, WHITE);
-    let selected_element_text = String::from("Selected element: ") + selected_element.to_string();
+    let selected = match brush_mode {
+        BrushMode::Element => selected_element.to_string(),
+        BrushMode::Wind => "Wind brush",
+        BrushMode::Vortex => "Vortex brush",
+    };
+    let selected_element_text = String::from("Selected element: ") + selected;
     draw_text(
         &selected_element_text,
         10.0,
@@ -171,15 +559,42 @@ fn draw_controls(control_manager: &ControlManager, selected_element: &Element, b
     let controls = control_manager.get_controls();
     let brush_controls = control_manager.get_brush_controls();
     let mut y_offset = top_of_text + 60.0;
+    // Element controls wrap into further columns once they reach the bottom of the screen,
+    // leaving room below the first column for the remaining controls
+    let bottom = screen_height() - 20.0 * (brush_controls.len() + EXTRA_CONTROLS.len()) as f32;
+    let mut x_offset = 10.0;
     for control in controls {
-        draw_text(&control.description, 10.0, y_offset, 20.0, WHITE);
+        if y_offset > bottom {
+            y_offset = top_of_text + 60.0;
+            x_offset += 160.0;
+        }
+        draw_text(&control.description, x_offset, y_offset, 20.0, WHITE);
         y_offset += 20.0;
     }
+    if x_offset > 10.0 {
+        y_offset = bottom;
+    }
     for brush_control in brush_controls {
         draw_text(&brush_control.description, 10.0, y_offset, 20.0, WHITE);
         y_offset += 20.0;
     }
-    draw_text("esc: quit, r: reset", 10.0, y_offset, 20.0, WHITE);
+    for line in EXTRA_CONTROLS {
+        draw_text(line, 10.0, y_offset, 20.0, WHITE);
+        y_offset += 20.0;
+    }
+}
+
+// List how much of each element the drains have consumed, in the top right corner
+fn draw_drained(grid: &Grid) {
+    let mut drained: Vec<_> = grid.drained.iter().collect();
+    drained.sort();
+    let x = screen_width() - 200.0;
+    let mut y_offset = 20.0;
+    draw_text("Drained:", x, y_offset, 20.0, WHITE);
+    for (name, count) in drained {
+        y_offset += 20.0;
+        draw_text(format!("{}: {}", name, count), x, y_offset, 20.0, WHITE);
+    }
 }
 
 fn draw_brush_box(grid: &Grid, brush_size: usize) {
@@ -196,7 +611,113 @@ fn draw_brush_box(grid: &Grid, brush_size: usize) {
     );
 }
 
-fn place_element(grid: &mut Grid, selected_element: &Element, brush_size: &mut usize) {
+// Drop a rigid body at the mouse position
+fn handle_body_input(grid: &mut Grid) {
+    let x = (mouse_position().0 / grid.cell_size) as usize;
+    let y = (mouse_position().1 / grid.cell_size) as usize;
+    if is_key_pressed(KeyCode::F2) {
+        grid.add_body(|id| RigidBody::rectangle(id, (x, y), 12, 12, &WOOD));
+    }
+    if is_key_pressed(KeyCode::F3) {
+        grid.add_body(|id| RigidBody::circle(id, (x, y), 6, &STONE));
+    }
+    if is_key_pressed(KeyCode::F4) {
+        grid.add_body(|id| RigidBody::rectangle(id, (x, y), 40, 3, &WOOD));
+    }
+}
+
+// Turn gravity a quarter turn or change how strong it is, down to zero-g
+fn handle_gravity_input(grid: &mut Grid) {
+    if is_key_pressed(KeyCode::F10) {
+        grid.gravity.rotate();
+    }
+    if is_key_pressed(KeyCode::F11) {
+        grid.gravity.strength = (grid.gravity.strength - GRAVITY_STEP).max(0.0);
+    }
+    if is_key_pressed(KeyCode::F12) {
+        grid.gravity.strength = (grid.gravity.strength + GRAVITY_STEP).min(MAX_GRAVITY);
+    }
+}
+
+// Grow or shrink the grid, keeping its proportions and everything in it that still fits
+fn handle_resize_input(grid: &mut Grid) {
+    let scale = if is_key_pressed(KeyCode::PageUp) {
+        RESIZE_STEP
+    } else if is_key_pressed(KeyCode::PageDown) {
+        1.0 / RESIZE_STEP
+    } else {
+        return;
+    };
+    let resized = |size: usize| clamp_grid_size((size as f32 * scale).round() as usize);
+    grid.resize(resized(grid.width), resized(grid.height));
+}
+
+// Cycle the edge of the world each arrow key points at between wall, void and wrap
+fn handle_boundary_input(grid: &mut Grid) {
+    let keys = [
+        (KeyCode::Left, Edge::Left),
+        (KeyCode::Right, Edge::Right),
+        (KeyCode::Up, Edge::Top),
+        (KeyCode::Down, Edge::Bottom),
+    ];
+    for (key, edge) in keys {
+        if is_key_pressed(key) {
+            grid.boundaries.cycle(edge);
+        }
+    }
+}
+
+// Paint wind with the mouse. The wind brush blows the way the mouse is being dragged and
+// the vortex brush swirls around the cursor. The right mouse button calms the wind.
+fn handle_wind_input(
+    grid: &mut Grid,
+    brush_mode: BrushMode,
+    brush_size: usize,
+    last_mouse_position: (f32, f32),
+) {
+    let (mouse_x, mouse_y) = mouse_position();
+    let center = (
+        (mouse_x / grid.cell_size) as usize,
+        (mouse_y / grid.cell_size) as usize,
+    );
+    // Big enough to always cover at least the region under the cursor
+    let radius = brush_size as f32 + WIND_REGION_SIZE as f32;
+    if is_mouse_button_down(MouseButton::Right) {
+        grid.wind.paint(center, radius, |_| (0.0, 0.0));
+        return;
+    }
+    if !is_mouse_button_down(MouseButton::Left) {
+        return;
+    }
+    match brush_mode {
+        BrushMode::Wind => {
+            let drag = (
+                mouse_x - last_mouse_position.0,
+                mouse_y - last_mouse_position.1,
+            );
+            let length = drag.0.hypot(drag.1);
+            if length > 0.0 {
+                let wind = (
+                    drag.0 / length * WIND_BRUSH_STRENGTH,
+                    drag.1 / length * WIND_BRUSH_STRENGTH,
+                );
+                grid.wind.paint(center, radius, |_| wind);
+            }
+        }
+        BrushMode::Vortex => {
+            grid.wind.paint(center, radius, |(dx, dy)| {
+                let distance = dx.hypot(dy).max(1.0);
+                (
+                    -dy / distance * WIND_BRUSH_STRENGTH,
+                    dx / distance * WIND_BRUSH_STRENGTH,
+                )
+            });
+        }
+        BrushMode::Element => {}
+    }
+}
+
+fn place_element(grid: &mut Grid, selected_element: &'static Element, brush_size: &usize) {
     let brush_offset = (*brush_size - 1) / 2;
     for i in 0..*brush_size {
         for j in 0..*brush_size {
@@ -207,7 +728,7 @@ fn place_element(grid: &mut Grid, selected_element: &Element, brush_size: &mut u
             if x >= 0 && y >= 0 {
                 let x = x as usize;
                 let y = y as usize;
-                grid.set((x, y), *selected_element);
+                grid.set((x, y), selected_element);
             }
         }
     }
@@ -215,7 +736,7 @@ fn place_element(grid: &mut Grid, selected_element: &Element, brush_size: &mut u
 
 struct Control {
     key: KeyCode,
-    action: Box<dyn Fn(&mut Element)>,
+    action: Box<dyn Fn(&mut &'static Element)>,
     description: String,
 }
 
@@ -241,7 +762,7 @@ impl ControlManager {
     fn add_control(
         &mut self,
         key: KeyCode,
-        action: Box<dyn Fn(&mut Element)>,
+        action: Box<dyn Fn(&mut &'static Element)>,
         description: String,
     ) {
         self.controls.push(Control {
@@ -259,7 +780,11 @@ impl ControlManager {
         });
     }
 
-    fn handle_input(&self, selected_element: &mut Element, brush_size: &mut usize) -> bool {
+    fn handle_input(
+        &self,
+        selected_element: &mut &'static Element,
+        brush_size: &mut usize,
+    ) -> bool {
         for control in &self.controls {
             if is_key_pressed(control.key) {
                 (control.action)(selected_element);
@@ -291,7 +816,10 @@ impl ControlManager {
         }
 
         //manually added controls
-        result += "\nesc: quit, r: reset";
+        for line in EXTRA_CONTROLS {
+            result += "\n";
+            result += line;
+        }
         result
     }
 
@@ -304,11 +832,11 @@ impl ControlManager {
     }
 }
 
-fn handle_mouse_input(grid: &mut Grid, selected_element: &Element, brush_size: &usize) {
+fn handle_mouse_input(grid: &mut Grid, selected_element: &'static Element, brush_size: &usize) {
     if is_mouse_button_down(MouseButton::Left) {
-        place_element(grid, &mut selected_element.clone(), &mut brush_size.clone());
+        place_element(grid, selected_element, brush_size);
     }
     if is_mouse_button_down(MouseButton::Right) {
-        place_element(grid, &NOTHING, &mut brush_size.clone());
+        place_element(grid, &NOTHING, brush_size);
     }
 }
@@ -0,0 +1,128 @@
+use crate::boundary::Boundary;
+use crate::element_type::TERMINAL_VELOCITY;
+use crate::elements::{Element, NOTHING};
+use crate::grid::{Grid, Vector2};
+
+// Fraction of the wind's speed a particle in flight is accelerated by each tick
+const WIND_ACCELERATION: f32 = 0.05;
+
+// Material that has been thrown out of the grid, e.g. by a splash or an explosion.
+// Particles fly freely until they hit something, at which point they turn back into a cell.
+#[derive(Clone, Copy)]
+pub struct Particle {
+    pub element: &'static Element,
+    // Position in cells, which may be above the top of the grid while in flight
+    pub position: (f32, f32),
+    // Velocity in cells per tick, positive y pointing down
+    pub velocity: (f32, f32),
+}
+
+// Advance every particle by one tick, settling the ones that landed back into the grid
+pub fn step_particles(grid: &mut Grid) {
+    let particles = std::mem::take(&mut grid.particles);
+    grid.particles = particles
+        .into_iter()
+        .filter_map(|particle| step_particle(grid, particle))
+        .collect();
+}
+
+// Move a particle along its path one sub-step at a time so it can't skip over thin walls.
+// Returns the particle if it is still in flight.
+fn step_particle(grid: &mut Grid, mut particle: Particle) -> Option<Particle> {
+    let gravity = grid.gravity;
+    let (ax, ay) = gravity.acceleration();
+    let velocity = (particle.velocity.0 + ax, particle.velocity.1 + ay);
+    let (across, down) = gravity.to_relative(velocity);
+    particle.velocity = gravity.from_relative((across, down.min(TERMINAL_VELOCITY)));
+    if let Some(pos) = cell_at(grid, particle.position) {
+        let wind = grid.wind.at(pos);
+        particle.velocity.0 += wind.0 * WIND_ACCELERATION;
+        particle.velocity.1 += wind.1 * WIND_ACCELERATION;
+    }
+    let (vx, vy) = particle.velocity;
+    let steps = vx.abs().max(vy.abs()).ceil().max(1.0) as usize;
+    let step = (vx / steps as f32, vy / steps as f32);
+
+    for _ in 0..steps {
+        let next = (particle.position.0 + step.0, particle.position.1 + step.1);
+        match locate(grid, next) {
+            Ok((next, pos)) if *grid.get(pos) == NOTHING => particle.position = next,
+            Ok(_) => {
+                settle(grid, &particle);
+                return None;
+            }
+            // Particles can fly up out of the world over a wall and fall back in, but they
+            // land against the other walls
+            Err(Boundary::Wall) if is_above_world(grid, next) => particle.position = next,
+            Err(Boundary::Wall) => {
+                settle(grid, &particle);
+                return None;
+            }
+            // Lost to the void
+            Err(_) => return None,
+        }
+    }
+    if gravity.is_zero_g() && cell_at(grid, particle.position).is_none() {
+        // Nothing will ever pull it back
+        return None;
+    }
+    Some(particle)
+}
+
+// The cell containing the given position, if it is within the grid
+fn cell_at(grid: &Grid, position: (f32, f32)) -> Option<Vector2> {
+    locate(grid, position).ok().map(|(_, pos)| pos)
+}
+
+// The given position taken through the edges of the world, along with the cell containing
+// it, or the boundary in the way if it is past a wall or the void
+fn locate(grid: &Grid, position: (f32, f32)) -> Result<((f32, f32), Vector2), Boundary> {
+    let (x, y) = (position.0.floor(), position.1.floor());
+    let pos = grid.resolve((x as isize, y as isize))?;
+    let wrapped = (
+        pos.0 as f32 + (position.0 - x),
+        pos.1 as f32 + (position.1 - y),
+    );
+    Ok((wrapped, pos))
+}
+
+// Whether the given position is past the edge of the world that gravity points away from,
+// and not past any of the other edges
+fn is_above_world(grid: &Grid, position: (f32, f32)) -> bool {
+    let overflow = |coordinate: f32, size: usize| {
+        if coordinate < 0.0 {
+            -1
+        } else if coordinate >= size as f32 {
+            1
+        } else {
+            0
+        }
+    };
+    let (dx, dy) = grid.gravity.down;
+    (
+        overflow(position.0, grid.width),
+        overflow(position.1, grid.height),
+    ) == (-dx, -dy)
+}
+
+// Turn a particle back into a cell at its position, or the first free cell above it.
+// Particles that can't find any room are lost.
+fn settle(grid: &mut Grid, particle: &Particle) {
+    let Some(mut pos) = cell_at(grid, particle.position) else {
+        return;
+    };
+    // Give up after a full lap of a world that wraps
+    for _ in 0..grid.width.max(grid.height) {
+        if *grid.get(pos) == NOTHING {
+            let gravity = grid.gravity;
+            let (_, down) = gravity.to_relative(particle.velocity);
+            grid.set(pos, particle.element);
+            grid.set_velocity(pos, gravity.from_relative((0.0, down)));
+            return;
+        }
+        match grid.above(pos) {
+            Some(above) => pos = above,
+            None => return,
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use crate::boundary::Boundaries;
+use crate::element_type::{ElementType, GRAVITY};
+use crate::elements::{Element, NOTHING};
+use crate::gravity::Gravity;
+use crate::grid::{Grid, Vector2};
+
+// Most cells moved per liquid body per tick, so big bodies settle gradually
+const MAX_TRANSFERS: usize = 8;
+// Pressure head, in cells, needed before liquid shoots out of an opening instead of just
+// welling up
+const FOUNTAIN_HEAD: usize = 4;
+// Fraction of the pressure head a fountain reaches, the rest being lost to friction
+const FOUNTAIN_EFFICIENCY: f32 = 0.7;
+
+// Liquids on their own only look at their immediate surroundings, so a U-shaped container
+// filled from one side would never level out. This finds every connected body of the same
+// liquid that has landed and moves cells from its surface to the lowest free spot next to
+// it. Only the part of a body that is walled in on both sides is under pressure: liquid
+// standing free, like a column that is still spreading out, just flows on its own. Liquid
+// pushed out of an opening more than a few cells below where the walls stop has enough
+// pressure to shoot upwards as a fountain.
+pub fn step_pressure(grid: &mut Grid) {
+    let (gravity, boundaries) = (grid.gravity, grid.boundaries);
+    if grid.level_bodies.gravity != gravity || grid.level_bodies.boundaries != boundaries {
+        // What counts as level depends on which way is down and where the edges lead
+        grid.level_bodies = LevelBodies::new(grid.width, grid.height);
+        grid.level_bodies.gravity = gravity;
+        grid.level_bodies.boundaries = boundaries;
+    }
+
+    let mut visited = Vec::new();
+    for index in 0..grid.width * grid.height {
+        let cell = &grid.cells()[index];
+        // Falling liquid isn't part of any body until it lands
+        if cell.element.element_type != ElementType::Liquid
+            || cell.free_falling
+            || grid.level_bodies.cells[index]
+        {
+            continue;
+        }
+        if visited.is_empty() {
+            // Only needed once there is some liquid to look at
+            visited = vec![false; grid.width * grid.height];
+        } else if visited[index] {
+            continue;
+        }
+        let start = (index % grid.width, index / grid.width);
+        let body = LiquidBody::find(grid, &mut visited, start, cell.element);
+        if !body.equalize(grid) {
+            for &(x, y) in &body.cells {
+                grid.level_bodies.cells[y * grid.width + x] = true;
+            }
+        }
+    }
+}
+
+// Liquid cells whose body was level the last time it was checked, along with the gravity
+// and boundaries it was checked under. A level body can't move anything until something in
+// or next to it changes, so it isn't flood filled again until then.
+pub struct LevelBodies {
+    cells: Vec<bool>,
+    gravity: Gravity,
+    boundaries: Boundaries,
+}
+
+impl LevelBodies {
+    pub fn new(width: usize, height: usize) -> LevelBodies {
+        LevelBodies {
+            cells: vec![false; width * height],
+            gravity: Gravity::NORMAL,
+            boundaries: Boundaries::WALLS,
+        }
+    }
+
+    // Something changed at the given index into the grid's cells, so the body there has to
+    // be checked again
+    pub fn disturb(&mut self, index: usize) {
+        if let Some(level) = self.cells.get_mut(index) {
+            *level = false;
+        }
+    }
+}
+
+struct LiquidBody {
+    element: &'static Element,
+    cells: Vec<Vector2>,
+    // Cells of the body on its surface, highest first
+    surface: Vec<Vector2>,
+    // Empty cells touching the body, lowest first
+    openings: Vec<Vector2>,
+    // Depths of the empty cells beside the body, highest first. Below a surface cell, the
+    // body is only walled in down to the first of these.
+    side_openings: Vec<usize>,
+}
+
+impl LiquidBody {
+    // Flood fill the body of liquid containing `start`
+    fn find(
+        grid: &Grid,
+        visited: &mut [bool],
+        start: Vector2,
+        element: &'static Element,
+    ) -> LiquidBody {
+        let mut cells = Vec::new();
+        let mut surface = Vec::new();
+        let mut openings = Vec::new();
+        let mut queue = vec![start];
+        visited[start.1 * grid.width + start.0] = true;
+
+        let mut side_openings = Vec::new();
+        let up = grid.gravity.relative((0, -1));
+        let down = grid.gravity.relative((0, 1));
+        while let Some(pos) = queue.pop() {
+            cells.push(pos);
+            let mut is_surface = false;
+            for offset in [(0, -1), (-1, 0), (1, 0), (0, 1)] {
+                let Some(neighbor) = grid.offset(pos, offset) else {
+                    continue;
+                };
+                let index = neighbor.1 * grid.width + neighbor.0;
+                if visited[index] {
+                    continue;
+                }
+                let neighbor_cell = &grid.cells()[index];
+                if *neighbor_cell.element == NOTHING {
+                    openings.push(neighbor);
+                    if offset == up {
+                        is_surface = true;
+                    } else if offset != down {
+                        side_openings.push(grid.depth(neighbor));
+                    }
+                } else if neighbor_cell.element == element && !neighbor_cell.free_falling {
+                    visited[index] = true;
+                    queue.push(neighbor);
+                }
+            }
+            if is_surface {
+                surface.push(pos);
+            }
+        }
+
+        surface.sort_by_key(|&pos| grid.depth(pos));
+        openings.sort_by_key(|&pos| (std::cmp::Reverse(grid.depth(pos)), pos));
+        openings.dedup();
+        side_openings.sort_unstable();
+        side_openings.dedup();
+        LiquidBody {
+            element,
+            cells,
+            surface,
+            openings,
+            side_openings,
+        }
+    }
+
+    // Pressure pushing liquid from the given surface cell out of the given opening: how far
+    // the surface is above the opening, or above where the body stops being walled in below
+    // it if that is higher. A free-standing column is open beside every cell, so it has no
+    // pressure at all and just spreads out on its own.
+    fn head(&self, grid: &Grid, from: Vector2, to: Vector2) -> usize {
+        let depth = grid.depth(from);
+        let mut level = grid.depth(to);
+        if let Some(&open) = self.side_openings.iter().find(|&&open| open > depth) {
+            level = level.min(open);
+        }
+        level.saturating_sub(depth)
+    }
+
+    // Move the highest surface cells into the lowest openings while the pressure is enough
+    // to bring them down by more than a cell. Returns whether anything moved.
+    fn equalize(&self, grid: &mut Grid) -> bool {
+        let moves = self.surface.iter().zip(self.openings.iter());
+        let gravity = grid.gravity;
+        let mut moved = false;
+        for (&from, &to) in moves.take(MAX_TRANSFERS) {
+            let head = self.head(grid, from, to);
+            if head <= 1 {
+                break;
+            }
+            if grid.get(from) != self.element || *grid.get(to) != NOTHING {
+                continue;
+            }
+            grid.move_element(from, to);
+            moved = true;
+
+            let above_is_open = grid
+                .above(to)
+                .is_some_and(|above| *grid.get(above) == NOTHING);
+            if head >= FOUNTAIN_HEAD && above_is_open {
+                // Just fast enough to climb most of the way back up to the surface
+                let pull = GRAVITY * gravity.strength;
+                let speed = (2.0 * pull * head as f32 * FOUNTAIN_EFFICIENCY).sqrt();
+                grid.eject(to, gravity.from_relative((0.0, -speed)));
+            }
+        }
+        moved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{STONE, WATER};
+
+    // Depth of the highest water cell between the given columns, if there is any
+    fn water_level(grid: &Grid, columns: std::ops::RangeInclusive<usize>) -> Option<usize> {
+        (0..grid.height).find(|&y| columns.clone().any(|x| *grid.get((x, y)) == WATER))
+    }
+
+    #[test]
+    fn free_standing_column_does_not_fountain() {
+        let mut grid = Grid::new(40, 40);
+        for y in 29..40 {
+            grid.set((20, y), &WATER);
+            grid.set_free_falling((20, y), false);
+        }
+
+        step_pressure(&mut grid);
+        assert!(grid.particles.is_empty());
+        assert!((29..40).all(|y| *grid.get((20, y)) == WATER));
+    }
+
+    #[test]
+    fn level_body_is_checked_again_once_disturbed() {
+        let mut grid = Grid::new(40, 40);
+        for x in 10..=20 {
+            for y in 30..=39 {
+                grid.set((x, y), &STONE);
+            }
+        }
+        for x in 12..=18 {
+            for y in 30..=37 {
+                grid.set((x, y), &WATER);
+                grid.set_free_falling((x, y), false);
+            }
+        }
+        step_pressure(&mut grid);
+        assert!(grid.level_bodies.cells[37 * grid.width + 15]);
+
+        // Knock a hole in the bottom of the wall, well below the surface
+        grid.set((11, 37), &NOTHING);
+        assert!(!grid.level_bodies.cells[37 * grid.width + 12]);
+        step_pressure(&mut grid);
+        assert!(*grid.get((11, 37)) == WATER);
+    }
+
+    #[test]
+    fn u_tube_levels_out() {
+        let mut grid = Grid::new(60, 40);
+        for x in 8..=24 {
+            for y in 10..=35 {
+                grid.set((x, y), &STONE);
+            }
+        }
+        // Two arms joined by a channel along the bottom, with the left one filled
+        for y in 10..=33 {
+            for x in (10..=12).chain(20..=22) {
+                grid.set((x, y), &NOTHING);
+            }
+        }
+        for x in 13..=19 {
+            for y in 30..=33 {
+                grid.set((x, y), &WATER);
+            }
+        }
+        for x in 10..=12 {
+            for y in 14..=33 {
+                grid.set((x, y), &WATER);
+            }
+        }
+
+        for _ in 0..500 {
+            grid.update();
+        }
+        let left = water_level(&grid, 10..=12).unwrap();
+        let right = water_level(&grid, 20..=22).unwrap();
+        assert!(
+            left.abs_diff(right) <= 1,
+            "left at {left}, right at {right}"
+        );
+    }
+}
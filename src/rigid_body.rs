@@ -0,0 +1,422 @@
+use crate::boundary::Boundary;
+use crate::element_type::{ElementType, GRAVITY, TERMINAL_VELOCITY};
+use crate::elements::{Element, NOTHING};
+use crate::grid::{Grid, Vector2};
+
+// Fraction of speed kept after bouncing off something
+const RESTITUTION: f32 = 0.2;
+// Fraction of sideways speed kept each tick while resting on something
+const SURFACE_FRICTION: f32 = 0.9;
+// Fraction of speed kept each tick per fully submerged body
+const LIQUID_DRAG: f32 = 0.9;
+// How strongly an off-center impact sets the body spinning
+const SPIN_RESPONSE: f32 = 0.3;
+// Fraction of angular velocity kept each tick
+const ANGULAR_DAMPING: f32 = 0.98;
+// Fastest a body can spin, in radians per tick
+const MAX_ANGULAR_VELOCITY: f32 = 0.2;
+
+// A solid object made of grid cells that moves and rotates as one. Each tick the body is
+// lifted out of the grid, moved by a simple rigid body solver, and drawn back into the grid
+// at its new position. While in the grid its cells are marked with the body's id, so they
+// aren't stepped like normal cells and everything else treats them as solid.
+pub struct RigidBody {
+    pub id: u32,
+    // Element of each cell, and its offset from the center of mass before rotation
+    cells: Vec<(&'static Element, (f32, f32))>,
+    // Where each cell was drawn into the grid last tick, if it fit
+    placed: Vec<Option<Vector2>>,
+    // Center of mass, in cells
+    pub position: (f32, f32),
+    // In cells per tick, positive y pointing down
+    pub velocity: (f32, f32),
+    // In radians, positive turning clockwise on screen
+    pub angle: f32,
+    pub angular_velocity: f32,
+}
+
+// Where a body's cells would land for a given pose
+struct Pose {
+    position: (f32, f32),
+    angle: f32,
+}
+
+impl RigidBody {
+    // Create a body from cells at the given grid positions
+    pub fn new(id: u32, cells: Vec<(Vector2, &'static Element)>) -> RigidBody {
+        let mass: f32 = cells.iter().map(|(_, element)| element.density).sum();
+        let center = cells.iter().fold((0.0, 0.0), |sum, ((x, y), element)| {
+            (
+                sum.0 + *x as f32 * element.density / mass,
+                sum.1 + *y as f32 * element.density / mass,
+            )
+        });
+        let placed = vec![None; cells.len()];
+        let cells = cells
+            .into_iter()
+            .map(|((x, y), element)| (element, (x as f32 - center.0, y as f32 - center.1)))
+            .collect();
+        RigidBody {
+            id,
+            cells,
+            placed,
+            position: center,
+            velocity: (0.0, 0.0),
+            angle: 0.0,
+            angular_velocity: 0.0,
+        }
+    }
+
+    // A solid rectangle of the given element centered on `center`
+    pub fn rectangle(
+        id: u32,
+        center: Vector2,
+        width: usize,
+        height: usize,
+        element: &'static Element,
+    ) -> RigidBody {
+        let mut cells = Vec::new();
+        for dy in 0..height {
+            for dx in 0..width {
+                let x = (center.0 + dx).checked_sub(width / 2);
+                let y = (center.1 + dy).checked_sub(height / 2);
+                if let (Some(x), Some(y)) = (x, y) {
+                    cells.push(((x, y), element));
+                }
+            }
+        }
+        RigidBody::new(id, cells)
+    }
+
+    // A solid disc of the given element centered on `center`
+    pub fn circle(id: u32, center: Vector2, radius: usize, element: &'static Element) -> RigidBody {
+        let r = radius as isize;
+        let mut cells = Vec::new();
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let x = center.0 as isize + dx;
+                let y = center.1 as isize + dy;
+                if dx * dx + dy * dy <= r * r && x >= 0 && y >= 0 {
+                    cells.push(((x as usize, y as usize), element));
+                }
+            }
+        }
+        RigidBody::new(id, cells)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // Whether the body could be drawn where it is without overlapping anything solid
+    pub fn fits(&self, grid: &Grid) -> bool {
+        self.collisions(grid, &self.pose()).is_empty()
+    }
+
+    // Move the body along with the contents of a grid being resized to the given size. Cells
+    // drawn where the grid no longer reaches were cropped along with it, so they are dropped
+    // from the body.
+    pub fn shift(&mut self, shift: (isize, isize), width: usize, height: usize) {
+        self.position.0 += shift.0 as f32;
+        self.position.1 += shift.1 as f32;
+        let mut index = 0;
+        while index < self.cells.len() {
+            let Some((x, y)) = self.placed[index] else {
+                index += 1;
+                continue;
+            };
+            let x = x as isize + shift.0;
+            let y = y as isize + shift.1;
+            if x >= 0 && y >= 0 && x < width as isize && y < height as isize {
+                self.placed[index] = Some((x as usize, y as usize));
+                index += 1;
+            } else {
+                self.cells.swap_remove(index);
+                self.placed.swap_remove(index);
+            }
+        }
+    }
+
+    // Whether none of the body's cells made it into the grid, e.g. because it fell into the void
+    fn is_lost(&self) -> bool {
+        self.placed.iter().all(Option::is_none)
+    }
+
+    fn mass(&self) -> f32 {
+        self.cells.iter().map(|(element, _)| element.density).sum()
+    }
+
+    fn moment_of_inertia(&self) -> f32 {
+        self.cells
+            .iter()
+            .map(|(element, (x, y))| element.density * (x * x + y * y))
+            .sum::<f32>()
+            .max(1.0)
+    }
+
+    fn pose(&self) -> Pose {
+        Pose {
+            position: self.position,
+            angle: self.angle,
+        }
+    }
+
+    // The grid position each cell lands on for the given pose, which may be off the grid
+    fn cell_positions(&self, pose: &Pose) -> impl Iterator<Item = (isize, isize)> + '_ {
+        let (sin, cos) = pose.angle.sin_cos();
+        let (px, py) = pose.position;
+        self.cells.iter().map(move |(_, (x, y))| {
+            (
+                (px + x * cos - y * sin).round() as isize,
+                (py + x * sin + y * cos).round() as isize,
+            )
+        })
+    }
+
+    // Offsets from the center of mass of the cells that would overlap something solid
+    fn collisions(&self, grid: &Grid, pose: &Pose) -> Vec<(f32, f32)> {
+        self.cell_positions(pose)
+            .filter(|&(x, y)| !can_overlap(grid, x, y))
+            .map(|(x, y)| (x as f32 - pose.position.0, y as f32 - pose.position.1))
+            .collect()
+    }
+
+    // Take the body's cells out of the grid, returning the positions they left empty. Cells
+    // that were destroyed or replaced while the body was in the grid are dropped from the body.
+    fn lift(&mut self, grid: &mut Grid) -> Vec<Vector2> {
+        let mut vacated = Vec::new();
+        let mut index = 0;
+        while index < self.cells.len() {
+            let still_there = self.placed[index].filter(|&pos| {
+                grid.get_cell(pos)
+                    .is_some_and(|cell| cell.body == Some(self.id))
+            });
+            match (self.placed[index], still_there) {
+                (_, Some(pos)) => {
+                    grid.set(pos, &NOTHING);
+                    vacated.push(pos);
+                    index += 1;
+                }
+                (Some(_), None) => {
+                    self.cells.swap_remove(index);
+                    self.placed.swap_remove(index);
+                }
+                (None, None) => index += 1,
+            }
+        }
+        vacated
+    }
+
+    // Draw the body's cells into the grid. Liquid and gas they land on is moved into the space
+    // the body just left, or thrown clear if there isn't any. Cells that would land on anything
+    // else are left out.
+    fn place(&mut self, grid: &mut Grid, mut vacated: Vec<Vector2>) {
+        let pose = self.pose();
+        let positions: Vec<Option<Vector2>> = self
+            .cell_positions(&pose)
+            .map(|pos| grid.resolve(pos).ok())
+            .collect();
+        vacated.retain(|&pos| !positions.contains(&Some(pos)));
+        for (index, pos) in positions.into_iter().enumerate() {
+            self.placed[index] = None;
+            let Some(pos) = pos else {
+                continue;
+            };
+            if grid.get_cell(pos).is_some_and(|cell| cell.body.is_some()) {
+                // Rounding put two of our cells in the same spot
+                continue;
+            }
+            if !can_overlap(grid, pos.0 as isize, pos.1 as isize) {
+                // Something solid moved in since the body last checked for collisions, so
+                // leave it be and try drawing this cell again next tick
+                continue;
+            }
+            if matches!(
+                grid.get(pos).element_type,
+                ElementType::Liquid | ElementType::Gas
+            ) {
+                match vacated.pop() {
+                    Some(free) => grid.move_element(pos, free),
+                    None => {
+                        let (across, down) = grid.gravity.to_relative(self.velocity);
+                        let velocity = grid.gravity.from_relative((across, down.min(0.0) - 1.0));
+                        grid.eject(pos, velocity);
+                    }
+                }
+            }
+            grid.set(pos, self.cells[index].0);
+            if let Some(cell) = grid.get_cell_mut(pos) {
+                cell.body = Some(self.id);
+            }
+            self.placed[index] = Some(pos);
+        }
+    }
+
+    // The placed cells that are below the surface of a liquid, along with that liquid. A cell
+    // is submerged when the first cell outside the body on its level, to either side, is liquid.
+    fn submerged_cells(&self, grid: &Grid) -> Vec<(Vector2, &'static Element)> {
+        let outside = |mut pos: Vector2, across: isize| {
+            let step = grid.gravity.relative((across, 0));
+            // A body can reach all the way around a world that wraps
+            for _ in 0..grid.width.max(grid.height) {
+                let Some(next) = grid.offset(pos, step) else {
+                    return &NOTHING;
+                };
+                pos = next;
+                match grid.get_cell(pos) {
+                    Some(cell) if cell.body == Some(self.id) => continue,
+                    Some(cell) => return cell.element,
+                    None => return &NOTHING,
+                }
+            }
+            &NOTHING
+        };
+        self.placed
+            .iter()
+            .flatten()
+            .filter_map(|&pos| {
+                [outside(pos, -1), outside(pos, 1)]
+                    .into_iter()
+                    .find(|element| element.element_type == ElementType::Liquid)
+                    .map(|liquid| (pos, liquid))
+            })
+            .collect()
+    }
+
+    // Apply gravity, buoyancy and drag, then move the body as far as it can go this tick
+    fn integrate(&mut self, grid: &Grid, submerged: &[(Vector2, &'static Element)]) {
+        let gravity = grid.gravity;
+        let mass = self.mass();
+        let inertia = self.moment_of_inertia();
+
+        // Every submerged cell is pushed up by the weight of the liquid it displaces, which
+        // also turns the body so its heavier side ends up lower
+        let mut buoyancy = (0.0, 0.0);
+        let mut buoyancy_torque = 0.0;
+        for &((x, y), liquid) in submerged {
+            let pull = GRAVITY * gravity.strength * liquid.density;
+            let force = (-gravity.down.0 as f32 * pull, -gravity.down.1 as f32 * pull);
+            let (rx, ry) = (x as f32 - self.position.0, y as f32 - self.position.1);
+            buoyancy = (buoyancy.0 + force.0, buoyancy.1 + force.1);
+            buoyancy_torque += rx * force.1 - ry * force.0;
+        }
+        let submerged = submerged.len();
+        let acceleration = gravity.acceleration();
+        self.velocity.0 += acceleration.0 + buoyancy.0 / mass;
+        self.velocity.1 += acceleration.1 + buoyancy.1 / mass;
+        self.angular_velocity += buoyancy_torque / inertia;
+        if submerged > 0 {
+            let drag = 1.0 - (1.0 - LIQUID_DRAG) * submerged as f32 / self.cells.len() as f32;
+            self.velocity = (self.velocity.0 * drag, self.velocity.1 * drag);
+            self.angular_velocity *= drag;
+        }
+        let (across, down) = gravity.to_relative(self.velocity);
+        self.velocity =
+            gravity.from_relative((across, down.clamp(-TERMINAL_VELOCITY, TERMINAL_VELOCITY)));
+        self.angular_velocity = (self.angular_velocity * ANGULAR_DAMPING)
+            .clamp(-MAX_ANGULAR_VELOCITY, MAX_ANGULAR_VELOCITY);
+
+        // Move in steps of at most a cell so the body can't pass through thin walls
+        let radius = self
+            .cells
+            .iter()
+            .map(|(_, (x, y))| (x * x + y * y).sqrt())
+            .fold(0.0, f32::max);
+        let travel = self
+            .velocity
+            .0
+            .abs()
+            .max(self.velocity.1.abs())
+            .max(self.angular_velocity.abs() * radius);
+        let steps = travel.ceil().max(1.0) as usize;
+        for _ in 0..steps {
+            let (across, down) = gravity.to_relative(self.velocity);
+            self.move_across(grid, across / steps as f32);
+            self.move_down(grid, down / steps as f32, mass, inertia);
+            self.rotate(grid, self.angular_velocity / steps as f32);
+        }
+    }
+
+    // Move sideways relative to gravity, bouncing off anything in the way
+    fn move_across(&mut self, grid: &Grid, distance: f32) {
+        let gravity = grid.gravity;
+        let (dx, dy) = gravity.from_relative((distance, 0.0));
+        let pose = Pose {
+            position: (self.position.0 + dx, self.position.1 + dy),
+            angle: self.angle,
+        };
+        if self.collisions(grid, &pose).is_empty() {
+            self.position = pose.position;
+        } else {
+            let (across, down) = gravity.to_relative(self.velocity);
+            self.velocity = gravity.from_relative((across * -RESTITUTION, down));
+        }
+    }
+
+    // Move along gravity, landing on anything in the way
+    fn move_down(&mut self, grid: &Grid, distance: f32, mass: f32, inertia: f32) {
+        let gravity = grid.gravity;
+        let (dx, dy) = gravity.from_relative((0.0, distance));
+        let pose = Pose {
+            position: (self.position.0 + dx, self.position.1 + dy),
+            angle: self.angle,
+        };
+        let contacts = self.collisions(grid, &pose);
+        if contacts.is_empty() {
+            self.position = pose.position;
+            return;
+        }
+
+        // The impact pushes back at the average contact point, so landing on a corner tips
+        // the body over towards its unsupported side
+        let (across, down) = gravity.to_relative(self.velocity);
+        let impulse = mass * down * (1.0 + RESTITUTION);
+        let contact_across = contacts
+            .iter()
+            .map(|&contact| gravity.to_relative(contact).0)
+            .sum::<f32>()
+            / contacts.len() as f32;
+        self.angular_velocity -= contact_across * impulse * SPIN_RESPONSE / inertia;
+        self.velocity = gravity.from_relative((across * SURFACE_FRICTION, down * -RESTITUTION));
+    }
+
+    fn rotate(&mut self, grid: &Grid, angle: f32) {
+        let pose = Pose {
+            position: self.position,
+            angle: self.angle + angle,
+        };
+        if self.collisions(grid, &pose).is_empty() {
+            self.angle = pose.angle;
+        } else {
+            self.angular_velocity *= -RESTITUTION;
+        }
+    }
+}
+
+// Bodies can move into empty space, liquids (which they push aside), gases and the void
+fn can_overlap(grid: &Grid, x: isize, y: isize) -> bool {
+    let pos = match grid.resolve((x, y)) {
+        Ok(pos) => pos,
+        Err(boundary) => return boundary == Boundary::Void,
+    };
+    let element = grid.get(pos);
+    *element == NOTHING
+        || matches!(
+            element.element_type,
+            ElementType::Liquid | ElementType::Gas | ElementType::Fire
+        )
+}
+
+// Move every rigid body by one tick. Bodies are handled one at a time, so each one collides
+// with the others where they were last drawn.
+pub fn step_bodies(grid: &mut Grid) {
+    let mut bodies = std::mem::take(&mut grid.bodies);
+    for body in bodies.iter_mut() {
+        let submerged = body.submerged_cells(grid);
+        let vacated = body.lift(grid);
+        body.integrate(grid, &submerged);
+        body.place(grid, vacated);
+    }
+    bodies.retain(|body| !body.is_empty() && !body.is_lost());
+    grid.bodies = bodies;
+}
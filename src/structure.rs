@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+
+use crate::boundary::Boundary;
+use crate::element_type::ElementType;
+use crate::grid::{Grid, Vector2};
+
+const UNREACHED: u16 = u16::MAX;
+
+// Structural elements are immovable solids that break into debris once they lose their
+// support. A structural cell is held up by a wall at the bottom of the world, an anchor
+// (any immovable element that isn't itself structural), or a chain of structural cells
+// leading to one. Resting on top of a supported cell is free, but every cell of overhang
+// sideways or hanging below costs one point of the element's strength. Cells past their
+// strength, and whole regions with no support at all, break loose and fall as debris.
+pub fn step_structure(grid: &mut Grid) {
+    let overhang = overhang_distances(grid);
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            if !is_structural(grid, (x, y)) {
+                continue;
+            }
+            let element = grid.get((x, y));
+            let Some(debris) = element.debris else {
+                continue;
+            };
+            if overhang[y * grid.width + x] as usize > element.strength {
+                grid.set((x, y), debris);
+            }
+        }
+    }
+}
+
+// Cells of rigid bodies move on their own, so they neither hold up nor belong to a structure
+fn is_structural(grid: &Grid, pos: Vector2) -> bool {
+    grid.get_cell(pos)
+        .is_some_and(|cell| cell.body.is_none() && cell.element.debris.is_some())
+}
+
+// Whether the position rests on a wall at the bottom of the world
+fn is_on_floor(grid: &Grid, pos: Vector2) -> bool {
+    let (dx, dy) = grid.gravity.relative((0, 1));
+    grid.resolve((pos.0 as isize + dx, pos.1 as isize + dy)) == Err(Boundary::Wall)
+}
+
+// Anything fixed in place that isn't structural holds up the structure touching it
+fn is_anchor(grid: &Grid, pos: Vector2) -> bool {
+    grid.get_cell(pos).is_some_and(|cell| {
+        cell.body.is_none()
+            && cell.element.debris.is_none()
+            && matches!(
+                cell.element.element_type,
+                ElementType::ImmovableSolid
+                    | ElementType::PixelGenerator
+                    | ElementType::PixelDestroyer
+            )
+    })
+}
+
+// For every structural cell, the least overhang between it and its support, found with a
+// 0-1 breadth first search. Cells with no support are left at UNREACHED.
+fn overhang_distances(grid: &Grid) -> Vec<u16> {
+    let mut distances = vec![UNREACHED; grid.width * grid.height];
+    let mut queue: VecDeque<Vector2> = VecDeque::new();
+
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            if !is_structural(grid, (x, y)) {
+                continue;
+            }
+            let on_anchor = neighbors(grid, (x, y)).any(|(pos, _)| is_anchor(grid, pos));
+            if is_on_floor(grid, (x, y)) || on_anchor {
+                distances[y * grid.width + x] = 0;
+                queue.push_back((x, y));
+            }
+        }
+    }
+
+    let up = grid.gravity.relative((0, -1));
+    while let Some((x, y)) = queue.pop_front() {
+        let distance = distances[y * grid.width + x];
+        for (pos, offset) in neighbors(grid, (x, y)) {
+            if !is_structural(grid, pos) {
+                continue;
+            }
+            // Building straight up is free, anything else is overhang
+            let cost = if offset == up { 0 } else { 1 };
+            let new_distance = distance.saturating_add(cost);
+            let index = pos.1 * grid.width + pos.0;
+            if new_distance < distances[index] {
+                distances[index] = new_distance;
+                if cost == 0 {
+                    queue.push_front(pos);
+                } else {
+                    queue.push_back(pos);
+                }
+            }
+        }
+    }
+    distances
+}
+
+// The in-bounds positions above, beside and below `pos`, along with their offset
+fn neighbors(grid: &Grid, pos: Vector2) -> impl Iterator<Item = (Vector2, (isize, isize))> + '_ {
+    [(0, -1), (-1, 0), (1, 0), (0, 1)]
+        .into_iter()
+        .filter_map(move |offset| Some((grid.offset(pos, offset)?, offset)))
+}
@@ -0,0 +1,131 @@
+use ::rand::{thread_rng, Rng};
+
+use crate::grid::{Grid, Vector2};
+
+// Side length in cells of the square regions the wind field is divided into
+pub const WIND_REGION_SIZE: usize = 8;
+// Strongest wind the field holds in any one region, in cells per tick
+pub const MAX_WIND: f32 = 2.0;
+
+// Wind over the world: a global wind that blows everywhere, plus a coarse grid of local
+// winds, one per region, painted in with the wind brushes. The wind at a cell is the sum of
+// the two. Wind doesn't move anything by itself, it only biases how cells that respond to
+// it move.
+pub struct WindField {
+    columns: usize,
+    rows: usize,
+    regions: Vec<(f32, f32)>,
+    pub global: (f32, f32),
+}
+
+impl WindField {
+    // A calm field covering a grid of the given size in cells
+    pub fn new(width: usize, height: usize) -> WindField {
+        let columns = width.div_ceil(WIND_REGION_SIZE);
+        let rows = height.div_ceil(WIND_REGION_SIZE);
+        WindField {
+            columns,
+            rows,
+            regions: vec![(0.0, 0.0); columns * rows],
+            global: (0.0, 0.0),
+        }
+    }
+
+    // The wind blowing at the given cell
+    pub fn at(&self, pos: Vector2) -> (f32, f32) {
+        let column = (pos.0 / WIND_REGION_SIZE).min(self.columns - 1);
+        let row = (pos.1 / WIND_REGION_SIZE).min(self.rows - 1);
+        let local = self.regions[row * self.columns + column];
+        (self.global.0 + local.0, self.global.1 + local.1)
+    }
+
+    // Set the local wind of every region whose center lies within `radius` cells of
+    // `center`. The wind for each region is given its center's offset from `center`.
+    pub fn paint<F>(&mut self, center: Vector2, radius: f32, wind: F)
+    where
+        F: Fn((f32, f32)) -> (f32, f32),
+    {
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let (x, y) = region_center(column, row);
+                let offset = (x - center.0 as f32, y - center.1 as f32);
+                if offset.0.hypot(offset.1) <= radius {
+                    let (wx, wy) = wind(offset);
+                    let strength = wx.hypot(wy);
+                    let scale = if strength > MAX_WIND {
+                        MAX_WIND / strength
+                    } else {
+                        1.0
+                    };
+                    self.regions[row * self.columns + column] = (wx * scale, wy * scale);
+                }
+            }
+        }
+    }
+
+    // Resize the field to cover a grid of the given size, moving the local winds along with
+    // the grid's contents by `shift` cells. Regions left uncovered are calm.
+    pub fn resize(&mut self, width: usize, height: usize, shift: (isize, isize)) {
+        let mut resized = WindField::new(width, height);
+        resized.global = self.global;
+        // Move by the nearest whole number of regions
+        let to_regions = |cells: isize| (cells as f32 / WIND_REGION_SIZE as f32).round() as isize;
+        let region_shift = (to_regions(shift.0), to_regions(shift.1));
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let new_column = column as isize + region_shift.0;
+                let new_row = row as isize + region_shift.1;
+                if new_column < 0
+                    || new_row < 0
+                    || new_column >= resized.columns as isize
+                    || new_row >= resized.rows as isize
+                {
+                    continue;
+                }
+                let index = new_row as usize * resized.columns + new_column as usize;
+                resized.regions[index] = self.regions[row * self.columns + column];
+            }
+        }
+        *self = resized;
+    }
+
+    // Remove all local and global wind
+    pub fn clear(&mut self) {
+        self.regions.fill((0.0, 0.0));
+        self.global = (0.0, 0.0);
+    }
+
+    // The center of every region in cells, along with the wind blowing there
+    pub fn regions(&self) -> impl Iterator<Item = ((f32, f32), (f32, f32))> + '_ {
+        (0..self.rows).flat_map(move |row| {
+            (0..self.columns).map(move |column| {
+                let center = region_center(column, row);
+                let wind = self.at((column * WIND_REGION_SIZE, row * WIND_REGION_SIZE));
+                (center, wind)
+            })
+        })
+    }
+}
+
+fn region_center(column: usize, row: usize) -> (f32, f32) {
+    let half = WIND_REGION_SIZE as f32 / 2.0;
+    (
+        (column * WIND_REGION_SIZE) as f32 + half,
+        (row * WIND_REGION_SIZE) as f32 + half,
+    )
+}
+
+// The direction, one of the eight neighbors, that the wind pushes the cell at `pos` in this
+// tick, if it pushes it at all. Stronger wind pushes more often, scaled by how much the
+// element responds to wind.
+pub fn wind_push(grid: &Grid, pos: Vector2, response: f32) -> Option<(isize, isize)> {
+    let (wx, wy) = grid.wind.at(pos);
+    let strength = wx.hypot(wy);
+    if strength == 0.0 || thread_rng().gen::<f32>() >= (strength * response).min(1.0) {
+        return None;
+    }
+    Some((
+        (wx / strength).round() as isize,
+        (wy / strength).round() as isize,
+    ))
+}